@@ -0,0 +1,90 @@
+use std::ops::Index;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// The number of items in the first segment, every next segment is twice as large
+const FIRST_SEGMENT_LEN: usize = 1024;
+/// Enough segments to never run out, only the segments that are used are allocated
+const SEGMENT_COUNT: usize = 40;
+
+/// A list that threads can push to and read from at the same time without locking it. Items are
+/// stored in segments that are never moved or freed while the arena lives, so a reference to an
+/// item stays valid while other items are added. An item can only be read once its push returned,
+/// its index should only be shared after that
+pub struct Arena<T> {
+    segments: [OnceLock<Box<[OnceLock<T>]>>; SEGMENT_COUNT],
+    len: AtomicUsize,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena {
+            segments: std::array::from_fn(|_| OnceLock::new()),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of items pushed so far, including pushes that are still in progress
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Adds the item created from its index and returns that index
+    pub fn push_with(&self, create: impl FnOnce(usize) -> T) -> usize {
+        let index = self.len.fetch_add(1, Ordering::Relaxed);
+        let (segment_index, offset) = Self::locate(index);
+        let segment = self.segments[segment_index].get_or_init(|| {
+            (0..FIRST_SEGMENT_LEN << segment_index)
+                .map(|_| OnceLock::new())
+                .collect()
+        });
+        // Every index is handed out once, so its slot is always empty
+        let _ = segment[offset].set(create(index));
+        index
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let (segment_index, offset) = Self::locate(index);
+        self.segments.get(segment_index)?.get()?[offset].get()
+    }
+
+    /// The segment holding the item with the given index, and the item's offset in it
+    fn locate(index: usize) -> (usize, usize) {
+        let position = index / FIRST_SEGMENT_LEN + 1;
+        let segment_index = (usize::BITS - 1 - position.leading_zeros()) as usize;
+        let segment_start = FIRST_SEGMENT_LEN * ((1 << segment_index) - 1);
+        (segment_index, index - segment_start)
+    }
+
+    /// The items in the order they were pushed
+    pub fn into_vec(self) -> Vec<T> {
+        let len = self.len.into_inner();
+        self.segments
+            .into_iter()
+            .filter_map(OnceLock::into_inner)
+            .flat_map(|segment| segment.into_vec())
+            .take(len)
+            .filter_map(OnceLock::into_inner)
+            .collect()
+    }
+}
+
+impl<T> From<Vec<T>> for Arena<T> {
+    fn from(items: Vec<T>) -> Arena<T> {
+        let arena = Arena::new();
+        for item in items {
+            arena.push_with(|_| item);
+        }
+        arena
+    }
+}
+
+impl<T> Index<usize> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        // Indices are only shared once their push returned
+        #[allow(clippy::unwrap_used)]
+        self.get(index).unwrap()
+    }
+}
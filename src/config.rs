@@ -0,0 +1,449 @@
+use std::{fs, path::Path, time::Duration};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// The number of searches used when no other search limit is given
+const DEFAULT_NUM_SEARCHES: u32 = 1000;
+
+/// How the threads of a search divide the work
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Parallelism {
+    /// Every thread searches its own tree, the statistics of their roots are merged afterwards
+    #[default]
+    Root,
+    /// All threads search one shared tree, using virtual loss to spread out over different paths
+    Tree,
+}
+
+/// How much weight RAVE values get compared to the normal values of a node, the weight `beta`
+/// starts at 1 and goes to 0 as the node gets more visits
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "schedule", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RaveSchedule {
+    /// `beta = sqrt(k / (3n + k))`, both values weigh the same after `equivalence` (k) visits
+    Equivalence { equivalence: f32 },
+    /// `beta = ñ / (n + ñ + 4b²nñ)`, with ñ the RAVE visits and `bias` (b) the expected
+    /// difference between the RAVE and the normal values
+    MinimumError { bias: f32 },
+}
+
+impl RaveSchedule {
+    /// The weight of the RAVE value for a node with `n` visits and `rave_n` RAVE visits
+    pub fn beta(&self, n: f32, rave_n: f32) -> f32 {
+        match *self {
+            RaveSchedule::Equivalence { equivalence } => {
+                f32::sqrt(equivalence / (3.0 * n + equivalence))
+            }
+            RaveSchedule::MinimumError { bias } => {
+                rave_n / (n + rave_n + 4.0 * bias * bias * n * rave_n)
+            }
+        }
+    }
+}
+
+/// The way the rollouts of the MCTS choose their moves, see `rollout::from_config`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Rollout {
+    /// Uniformly random moves
+    #[default]
+    Uniform,
+    /// Takes a winning move when there is one, otherwise blocks a winning move of the opponent
+    WinOrBlock,
+    /// The move with the best position heuristic, or a random move with a chance of `epsilon`
+    EpsilonGreedy { epsilon: f32 },
+    /// Random moves until `depth` moves are played, the position is then valued by the heuristic
+    Truncated { depth: usize },
+}
+
+/// The formula the MCTS selects children with, see `selection::from_config`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Selection {
+    #[default]
+    Ucb1,
+    /// UCB1 that explores children with consistent values less
+    Ucb1Tuned,
+    /// Weighs the exploration of every child by its prior
+    Puct,
+    /// Samples the Beta posterior of the win rate of every child
+    Thompson,
+    /// The child with the best mean value, or a random child with a chance of `epsilon`
+    EpsilonGreedy { epsilon: f32 },
+}
+
+/// How the MCTS chooses the move to play once it is done searching, see `final_move::choose_action`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum FinalMove {
+    /// The most visited move, also called the robust child
+    #[default]
+    MaxVisits,
+    /// The move with the best mean value
+    MaxValue,
+    /// The most visited move once it also has the best mean value, searching up to
+    /// `max_extra_searches` more times until they agree
+    MaxRobust { max_extra_searches: u32 },
+    /// The move with the best lower confidence bound, `mean value - confidence / sqrt(visits)`
+    SecureChild { confidence: f32 },
+    /// A random move, chosen with a chance proportional to `visits^(1 / temperature)`
+    Temperature { temperature: f32 },
+}
+
+/// The settings used by the MCTS, can be created with the builder or loaded from a TOML/JSON file
+/// A search stops as soon as any of its limits is reached, when no limit is given it stops after
+/// `DEFAULT_NUM_SEARCHES` searches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MctsConfig {
+    /// The constant representing the confidence of the model, the higher this value, the less the win_odds matters in the UCB formula
+    /// Lower means Exploitation, higher means Exploration
+    #[serde(default = "default_exploration_constant")]
+    exploration_constant: f32,
+    #[serde(default)]
+    num_searches: Option<u32>,
+    #[serde(default)]
+    max_duration_ms: Option<u64>,
+    /// The maximum number of nodes in the tree, including those kept from previous searches
+    #[serde(default)]
+    max_nodes: Option<usize>,
+    /// The seed for the random choices of the search, a random seed is used when none is given
+    #[serde(default)]
+    seed: Option<u64>,
+    /// The number of threads searching in parallel, with root parallelization the search limits
+    /// apply to every tree, with tree parallelization they apply to the shared tree
+    #[serde(default = "default_num_threads")]
+    num_threads: usize,
+    #[serde(default)]
+    parallelism: Parallelism,
+    /// Whether positions reached through different move orders share one node, turning the tree
+    /// into a graph. Only for games where a position can never repeat
+    #[serde(default)]
+    transpositions: bool,
+    /// Whether the root only searches one move of every group of moves that are symmetric, as
+    /// they lead to the same game
+    #[serde(default)]
+    merge_symmetric_moves: bool,
+    /// MCTS-Solver, marks finished games as proven wins, losses or draws and proves the nodes
+    /// above them, so proven moves are always played or avoided
+    #[serde(default)]
+    solver: bool,
+    /// Rapid Action Value Estimation, blending in the values of moves played later on in the same
+    /// searches, which speeds up placement games. Not used when None
+    #[serde(default)]
+    rave: Option<RaveSchedule>,
+    /// How the rollouts choose their moves, uniformly random by default
+    #[serde(default)]
+    rollout: Rollout,
+    /// How the children are selected, UCB1 by default. The exploration constant is used by the
+    /// UCB1, UCB1-Tuned and PUCT formulas
+    #[serde(default)]
+    selection: Selection,
+    /// How the move to play is chosen after searching, the most visited move by default
+    #[serde(default)]
+    final_move: FinalMove,
+}
+
+fn default_exploration_constant() -> f32 {
+    f32::sqrt(2.0)
+}
+
+fn default_num_threads() -> usize {
+    1
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self::without_limits().with_default_limit()
+    }
+}
+
+impl MctsConfig {
+    #[allow(dead_code)] // Used inside tests
+    pub fn builder() -> MctsConfigBuilder {
+        MctsConfigBuilder {
+            config: MctsConfig::without_limits(),
+        }
+    }
+
+    fn without_limits() -> MctsConfig {
+        MctsConfig {
+            exploration_constant: default_exploration_constant(),
+            num_searches: None,
+            max_duration_ms: None,
+            max_nodes: None,
+            seed: None,
+            num_threads: default_num_threads(),
+            parallelism: Parallelism::default(),
+            transpositions: false,
+            merge_symmetric_moves: false,
+            solver: false,
+            rave: None,
+            rollout: Rollout::Uniform,
+            selection: Selection::Ucb1,
+            final_move: FinalMove::MaxVisits,
+        }
+    }
+
+    fn with_default_limit(mut self) -> MctsConfig {
+        if self.num_searches.is_none() && self.max_duration_ms.is_none() && self.max_nodes.is_none()
+        {
+            self.num_searches = Some(DEFAULT_NUM_SEARCHES);
+        }
+        self
+    }
+
+    /// Replaces the seed, used to give every tree of a parallel search its own seed
+    pub fn with_seed(mut self, seed: u64) -> MctsConfig {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn exploration_constant(&self) -> f32 {
+        self.exploration_constant
+    }
+
+    pub fn num_searches(&self) -> Option<u32> {
+        self.num_searches
+    }
+
+    pub fn max_duration(&self) -> Option<Duration> {
+        self.max_duration_ms.map(Duration::from_millis)
+    }
+
+    pub fn max_nodes(&self) -> Option<usize> {
+        self.max_nodes
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    pub fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    pub fn transpositions(&self) -> bool {
+        self.transpositions
+    }
+
+    pub fn merge_symmetric_moves(&self) -> bool {
+        self.merge_symmetric_moves
+    }
+
+    pub fn solver(&self) -> bool {
+        self.solver
+    }
+
+    pub fn rave(&self) -> Option<RaveSchedule> {
+        self.rave
+    }
+
+    pub fn rollout(&self) -> Rollout {
+        self.rollout
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    pub fn final_move(&self) -> FinalMove {
+        self.final_move
+    }
+
+    /// Loads the config from the given file, the format is based on its extension
+    pub fn from_file(path: impl AsRef<Path>) -> Result<MctsConfig> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read config file: {:?}", path))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            Some("json") => Self::from_json(&contents),
+            _ => Err(anyhow!(
+                "Unsupported config file: {:?}, expected a '.toml' or '.json' file",
+                path
+            )),
+        }
+    }
+
+    pub fn from_toml(contents: &str) -> Result<MctsConfig> {
+        let config: MctsConfig = toml::from_str(contents).context("Invalid TOML config")?;
+        let config = config.with_default_limit();
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_json(contents: &str) -> Result<MctsConfig> {
+        let config: MctsConfig = serde_json::from_str(contents).context("Invalid JSON config")?;
+        let config = config.with_default_limit();
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.num_searches == Some(0) {
+            bail!("The number of searches must be at least 1");
+        }
+
+        if self.max_duration_ms == Some(0) {
+            bail!("The maximum search duration must be at least 1 millisecond");
+        }
+
+        // The root node is always there, so at least 1 new node is needed to search anything
+        if matches!(self.max_nodes, Some(max_nodes) if max_nodes < 2) {
+            bail!("The maximum number of nodes must be at least 2");
+        }
+
+        if self.num_threads == 0 {
+            bail!("The number of threads must be at least 1");
+        }
+
+        match self.rave {
+            Some(RaveSchedule::Equivalence { equivalence })
+                if !equivalence.is_finite() || equivalence <= 0.0 =>
+            {
+                bail!("The RAVE equivalence must be above 0, got: {}", equivalence)
+            }
+            Some(RaveSchedule::MinimumError { bias }) if !bias.is_finite() || bias < 0.0 => {
+                bail!("The RAVE bias must be a positive number, got: {}", bias)
+            }
+            _ => {}
+        }
+
+        if let Rollout::EpsilonGreedy { epsilon } = self.rollout {
+            if !(0.0..=1.0).contains(&epsilon) {
+                bail!(
+                    "The rollout epsilon must be between 0 and 1, got: {}",
+                    epsilon
+                );
+            }
+        }
+
+        if let Selection::EpsilonGreedy { epsilon } = self.selection {
+            if !(0.0..=1.0).contains(&epsilon) {
+                bail!(
+                    "The selection epsilon must be between 0 and 1, got: {}",
+                    epsilon
+                );
+            }
+        }
+
+        match self.final_move {
+            FinalMove::SecureChild { confidence }
+                if !confidence.is_finite() || confidence < 0.0 =>
+            {
+                bail!(
+                    "The secure child confidence must be a positive number, got: {}",
+                    confidence
+                )
+            }
+            FinalMove::Temperature { temperature }
+                if !temperature.is_finite() || temperature <= 0.0 =>
+            {
+                bail!("The temperature must be above 0, got: {}", temperature)
+            }
+            _ => {}
+        }
+
+        if !self.exploration_constant.is_finite() || self.exploration_constant < 0.0 {
+            bail!(
+                "The exploration constant must be a positive number, got: {}",
+                self.exploration_constant
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[allow(dead_code)] // Used inside tests
+pub struct MctsConfigBuilder {
+    config: MctsConfig,
+}
+
+#[allow(dead_code)] // Used inside tests
+impl MctsConfigBuilder {
+    pub fn exploration_constant(mut self, exploration_constant: f32) -> Self {
+        self.config.exploration_constant = exploration_constant;
+        self
+    }
+
+    pub fn num_searches(mut self, num_searches: u32) -> Self {
+        self.config.num_searches = Some(num_searches);
+        self
+    }
+
+    /// Limits the search by wall-clock time, rounded down to whole milliseconds
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.config.max_duration_ms = Some(max_duration.as_millis() as u64);
+        self
+    }
+
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.config.max_nodes = Some(max_nodes);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.config.num_threads = num_threads;
+        self
+    }
+
+    pub fn parallelism(mut self, parallelism: Parallelism) -> Self {
+        self.config.parallelism = parallelism;
+        self
+    }
+
+    pub fn transpositions(mut self, transpositions: bool) -> Self {
+        self.config.transpositions = transpositions;
+        self
+    }
+
+    pub fn merge_symmetric_moves(mut self, merge_symmetric_moves: bool) -> Self {
+        self.config.merge_symmetric_moves = merge_symmetric_moves;
+        self
+    }
+
+    pub fn solver(mut self, solver: bool) -> Self {
+        self.config.solver = solver;
+        self
+    }
+
+    pub fn rave(mut self, schedule: RaveSchedule) -> Self {
+        self.config.rave = Some(schedule);
+        self
+    }
+
+    pub fn rollout(mut self, rollout: Rollout) -> Self {
+        self.config.rollout = rollout;
+        self
+    }
+
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.config.selection = selection;
+        self
+    }
+
+    pub fn final_move(mut self, final_move: FinalMove) -> Self {
+        self.config.final_move = final_move;
+        self
+    }
+
+    pub fn build(self) -> Result<MctsConfig> {
+        let config = self.config.with_default_limit();
+        config.validate()?;
+        Ok(config)
+    }
+}
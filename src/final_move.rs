@@ -0,0 +1,120 @@
+use rand::distr::weighted::WeightedIndex;
+use rand::distr::Distribution;
+use rand::rngs::StdRng;
+
+use crate::{
+    config::FinalMove,
+    mcts::{ActionStatistics, Proof},
+};
+
+/// The number of searches a max-robust search adds at a time, before checking again whether the
+/// most visited move has the best mean value
+const ROBUST_SEARCH_BATCH: u32 = 100;
+
+/// Chooses the move to play out of the statistics of the root actions, following the strategy of
+/// the config. A proven win is always played and proven losses are avoided when possible, so the
+/// strategy only chooses between the other actions. Ties go to the first action, None when no
+/// action has been searched
+pub fn choose_action<A: Copy>(
+    strategy: FinalMove,
+    actions: &[ActionStatistics<A>],
+    rng: &mut StdRng,
+) -> Option<A> {
+    if let Some(win) = actions
+        .iter()
+        .find(|statistics| statistics.proof == Proof::Win)
+    {
+        return Some(win.action);
+    }
+    let not_lost: Vec<ActionStatistics<A>> = actions
+        .iter()
+        .filter(|statistics| statistics.proof != Proof::Loss)
+        .cloned()
+        .collect();
+    let actions = if not_lost.is_empty() {
+        actions
+    } else {
+        &not_lost
+    };
+
+    let best_by = |score: &dyn Fn(&ActionStatistics<A>) -> f32| {
+        let mut best: Option<(A, f32)> = None;
+        for statistics in actions {
+            let score = score(statistics);
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((statistics.action, score));
+            }
+        }
+        best.map(|(action, _)| action)
+    };
+
+    match strategy {
+        // A search that could not agree plays the most visited move
+        FinalMove::MaxVisits | FinalMove::MaxRobust { .. } => {
+            best_by(&|statistics| statistics.visit_count as f32)
+        }
+        FinalMove::MaxValue => best_by(&|statistics| visited_mean_value(statistics)),
+        FinalMove::SecureChild { confidence } => best_by(&|statistics| {
+            visited_mean_value(statistics) - confidence / (statistics.visit_count as f32).sqrt()
+        }),
+        FinalMove::Temperature { temperature } => {
+            let max_visits = actions
+                .iter()
+                .map(|statistics| statistics.visit_count)
+                .max()?;
+            // Scaled by the most visits first, so that low temperatures do not overflow
+            let weights = actions.iter().map(|statistics| {
+                (statistics.visit_count as f64 / max_visits as f64).powf(1.0 / temperature as f64)
+            });
+
+            match WeightedIndex::new(weights) {
+                Ok(distribution) => Some(actions[distribution.sample(rng)].action),
+                Err(_) => best_by(&|statistics| statistics.visit_count as f32),
+            }
+        }
+    }
+}
+
+/// Whether the most visited action also has the best mean value, after which a max-robust search
+/// can stop
+pub fn is_robust<A>(actions: &[ActionStatistics<A>]) -> bool {
+    let most_visited = actions.iter().reduce(|most_visited, statistics| {
+        if statistics.visit_count > most_visited.visit_count {
+            statistics
+        } else {
+            most_visited
+        }
+    });
+    let Some(most_visited) = most_visited else {
+        return true;
+    };
+
+    actions
+        .iter()
+        .all(|statistics| visited_mean_value(statistics) <= most_visited.mean_value)
+}
+
+/// Keeps the searcher searching in batches with `search_more` until the actions returned by
+/// `action_statistics` are robust or `max_extra_searches` searches have been added
+pub fn search_until_robust<S, A>(
+    searcher: &mut S,
+    max_extra_searches: u32,
+    action_statistics: impl Fn(&S) -> Vec<ActionStatistics<A>>,
+    search_more: impl Fn(&mut S, u32),
+) {
+    let mut extra_searches = 0;
+    while extra_searches < max_extra_searches && !is_robust(&action_statistics(searcher)) {
+        let batch = ROBUST_SEARCH_BATCH.min(max_extra_searches - extra_searches);
+        search_more(searcher, batch);
+        extra_searches += batch;
+    }
+}
+
+/// The mean value of the action, unvisited actions have no value yet and are never preferred
+fn visited_mean_value<A>(statistics: &ActionStatistics<A>) -> f32 {
+    if statistics.visit_count > 0 {
+        statistics.mean_value
+    } else {
+        f32::NEG_INFINITY
+    }
+}
@@ -1,12 +1,404 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
 
-use anyhow::{anyhow, Result};
-use ndarray::{Array2, Axis};
+use anyhow::{anyhow, bail, Result};
+use ndarray::{s, Array2};
+use serde::Serialize;
 
-#[derive(Debug)]
+use crate::symmetry::{self, Symmetry};
+
+/// One of the two players, X always moves first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Player {
+    X,
+    O,
+}
+
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+
+    /// The value of the player's pieces on a board, empty cells are 0
+    pub fn cell(self) -> i8 {
+        match self {
+            Player::X => 1,
+            Player::O => -1,
+        }
+    }
+
+    /// The player whose piece has the given value on a board, None for an empty cell
+    pub fn from_cell(cell: i8) -> Option<Player> {
+        match cell {
+            1 => Some(Player::X),
+            -1 => Some(Player::O),
+            _ => None,
+        }
+    }
+
+    /// The player to move after the given numbers of pieces were played, taking turns from X
+    fn to_move(x_count: usize, o_count: usize) -> Player {
+        if x_count > o_count {
+            Player::O
+        } else {
+            Player::X
+        }
+    }
+
+    /// The player to move on a board where X moved first and the players took turns
+    fn to_move_on(board: &Array2<i8>) -> Player {
+        let count = |player: Player| board.iter().filter(|&&cell| cell == player.cell()).count();
+        Player::to_move(count(Player::X), count(Player::O))
+    }
+}
+
+impl Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for Player {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Player> {
+        match input.trim().to_lowercase().as_str() {
+            "x" => Ok(Player::X),
+            "o" => Ok(Player::O),
+            _ => bail!("please choose one of these: 'X'/'x' or 'O'/'o'"),
+        }
+    }
+}
+
+/// A move placing a piece on the cell at (row, column). Shown like the (row, column) position it
+/// wraps and ordered row by row
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Move(pub usize, pub usize);
+
+impl Debug for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
+}
+
+impl From<(usize, usize)> for Move {
+    fn from((row, column): (usize, usize)) -> Move {
+        Move(row, column)
+    }
+}
+
+impl From<Move> for (usize, usize) {
+    fn from(Move(row, column): Move) -> (usize, usize) {
+        (row, column)
+    }
+}
+
+/// How a game stands after a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome<P> {
+    /// The game is over and won by the player
+    Win(P),
+    /// The game is over without a winner
+    Draw,
+    Ongoing,
+}
+
+impl<P: PartialEq> Outcome<P> {
+    pub fn is_over(&self) -> bool {
+        *self != Outcome::Ongoing
+    }
+
+    /// The reward of the outcome for the given player, the one scale all values of the MCTS use:
+    /// 1.0 for a win, -1.0 for a loss and 0.0 for a draw. A game that is not over yet has no
+    /// reward, which counts as 0.0
+    pub fn reward(&self, player: P) -> f32 {
+        match self {
+            Outcome::Win(winner) if *winner == player => 1.0,
+            Outcome::Win(_) => -1.0,
+            Outcome::Draw | Outcome::Ongoing => 0.0,
+        }
+    }
+}
+
+/// A two player, turn based game that can be searched by the MCTS, the game and its types are
+/// shared between the threads of a parallel search
+pub trait Game: Send + Sync {
+    type State: Clone + PartialEq + Hash + Debug + Send + Sync;
+    type Action: Copy + PartialEq + Debug + Send + Sync;
+    type Player: Copy + PartialEq + Hash + Debug + Send + Sync;
+
+    fn initial_state(&self) -> Self::State;
+
+    fn legal_actions(&self, state: &Self::State) -> Vec<Self::Action>;
+
+    /// Returns the state after the given player has played the given action
+    fn apply(&self, state: &Self::State, player: Self::Player, action: Self::Action)
+        -> Self::State;
+
+    /// Returns the outcome of the state, where the given player made the last move and so is the
+    /// only player that can have just won
+    fn outcome(&self, state: &Self::State, player: Self::Player) -> Outcome<Self::Player>;
+
+    fn opponent(&self, player: Self::Player) -> Self::Player;
+
+    /// The Zobrist hash of the state, kept up to date by every move so reading it is free. Equal
+    /// states have equal hashes and different states almost never do, so it can key tables of
+    /// positions, as long as a collision only costs a wrong hint
+    fn state_hash(&self, state: &Self::State) -> u64;
+
+    /// A static guess of the value of an unfinished state for the player that just moved, between
+    /// -1.0 and 1.0 like the values of the MCTS. Games without a heuristic guess a draw
+    fn evaluate(&self, _state: &Self::State, _player: Self::Player) -> f32 {
+        0.0
+    }
+
+    /// Keeps one action of every group of actions that lead to symmetric positions in the given
+    /// state, games without symmetries keep every action
+    fn distinct_actions(
+        &self,
+        _state: &Self::State,
+        actions: Vec<Self::Action>,
+    ) -> Vec<Self::Action> {
+        actions
+    }
+}
+
+/// A state of the game together with the player to move in it, its outcome and the moves played
+/// on it, so whose turn it is never has to be tracked next to the state
+pub struct Position<G: Game> {
+    state: G::State,
+    to_move: G::Player,
+    /// The outcome of the state, decided when the position is created or changed
+    outcome: Outcome<G::Player>,
+    /// The moves played with `play`, with the state before each of them so they can be undone
+    history: Vec<PlayedMove<G>>,
+}
+
+struct PlayedMove<G: Game> {
+    player: G::Player,
+    action: G::Action,
+    previous_state: G::State,
+}
+
+impl<G: Game> Position<G> {
+    /// The position where the given player is to move in the given state, without a history
+    pub fn new(game: &G, state: G::State, to_move: G::Player) -> Position<G> {
+        let outcome = game.outcome(&state, game.opponent(to_move));
+        Position {
+            state,
+            to_move,
+            outcome,
+            history: vec![],
+        }
+    }
+
+    pub fn state(&self) -> &G::State {
+        &self.state
+    }
+
+    pub fn to_move(&self) -> G::Player {
+        self.to_move
+    }
+
+    /// The player that made the last move, the only player that can have just won
+    pub fn last_mover(&self, game: &G) -> G::Player {
+        game.opponent(self.to_move)
+    }
+
+    pub fn outcome(&self) -> Outcome<G::Player> {
+        self.outcome
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_over()
+    }
+
+    /// The moves played on this position with `play`, oldest first, with the player of each move
+    pub fn history(&self) -> impl Iterator<Item = (G::Player, G::Action)> + '_ {
+        self.history
+            .iter()
+            .map(|played_move| (played_move.player, played_move.action))
+    }
+
+    /// Plays the given legal action for the player to move, after which the opponent is to move
+    pub fn play(&mut self, game: &G, action: G::Action) {
+        let state = game.apply(&self.state, self.to_move, action);
+        self.history.push(PlayedMove {
+            player: self.to_move,
+            action,
+            previous_state: std::mem::replace(&mut self.state, state),
+        });
+        self.outcome = game.outcome(&self.state, self.to_move);
+        self.to_move = game.opponent(self.to_move);
+    }
+
+    /// Takes back the last move played with `play` and returns it, None when there is none
+    #[allow(dead_code)] // Used inside tests
+    pub fn undo(&mut self) -> Option<G::Action> {
+        let played_move = self.history.pop()?;
+        self.state = played_move.previous_state;
+        self.to_move = played_move.player;
+        // Moves are only played while the game is ongoing
+        self.outcome = Outcome::Ongoing;
+        Some(played_move.action)
+    }
+
+    /// The position after the given legal action, without the history of this position. Used
+    /// where the moves leading to a position are kept elsewhere, such as in a search tree
+    pub fn child(&self, game: &G, action: G::Action) -> Position<G> {
+        let state = game.apply(&self.state, self.to_move, action);
+        Position {
+            outcome: game.outcome(&state, self.to_move),
+            state,
+            to_move: game.opponent(self.to_move),
+            history: vec![],
+        }
+    }
+}
+
+impl<G: Game> Clone for Position<G> {
+    fn clone(&self) -> Self {
+        Position {
+            state: self.state.clone(),
+            to_move: self.to_move,
+            outcome: self.outcome,
+            history: self
+                .history
+                .iter()
+                .map(|played_move| PlayedMove {
+                    player: played_move.player,
+                    action: played_move.action,
+                    previous_state: played_move.previous_state.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Positions are the same when the same player is to move in the same state, however they were
+/// reached
+impl<G: Game> PartialEq for Position<G> {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_move == other.to_move && self.state == other.state
+    }
+}
+
+impl<G: Game> Debug for Position<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Position")
+            .field("state", &self.state)
+            .field("to_move", &self.to_move)
+            .field("outcome", &self.outcome)
+            .finish()
+    }
+}
+
+/// The random key of a piece of the given player on the given cell, XOR-ed into the Zobrist hash
+/// of a board while the piece is there, so an empty board hashes to 0. The keys are derived from
+/// the cell and player instead of stored, so every board size and game hashes the same pieces the
+/// same way
+fn zobrist_key(cell_index: usize, player: Player) -> u64 {
+    // SplitMix64, which spreads consecutive inputs over all bits
+    let mut key = ((cell_index as u64) << 1 | u64::from(player == Player::O))
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    key = (key ^ (key >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    key ^ (key >> 31)
+}
+
+/// A board of cells holding the `Player::cell` of the piece there, or 0 when empty. It reads like
+/// the `Array2` it wraps, but pieces are only placed with `place`, which keeps the Zobrist hash of
+/// the board up to date. Boards are hashed and told apart by that hash first
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct Board {
+    cells: Array2<i8>,
+    #[serde(skip)]
+    hash: u64,
+}
+
+impl Board {
+    pub fn empty(row_count: usize, column_count: usize) -> Board {
+        Board {
+            cells: Array2::zeros([row_count, column_count]),
+            hash: 0,
+        }
+    }
+
+    /// Places the player's piece on the given cell, replacing the piece that was there
+    pub fn place(&mut self, position: (usize, usize), player: Player) {
+        self.remove(position);
+        self.hash ^= zobrist_key(self.cell_index(position), player);
+        self.cells[position] = player.cell();
+    }
+
+    /// Removes the piece on the given cell, if there is one
+    pub fn remove(&mut self, position: (usize, usize)) {
+        if let Some(player) = Player::from_cell(self.cells[position]) {
+            self.hash ^= zobrist_key(self.cell_index(position), player);
+            self.cells[position] = 0;
+        }
+    }
+
+    fn cell_index(&self, (row, column): (usize, usize)) -> usize {
+        row * self.cells.ncols() + column
+    }
+
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// Hashes the board from scratch
+impl From<Array2<i8>> for Board {
+    fn from(cells: Array2<i8>) -> Board {
+        let column_count = cells.ncols();
+        let hash = cells
+            .indexed_iter()
+            .filter_map(|((row, column), &cell)| {
+                Player::from_cell(cell)
+                    .map(|player| zobrist_key(row * column_count + column, player))
+            })
+            .fold(0, |hash, key| hash ^ key);
+
+        Board { cells, hash }
+    }
+}
+
+impl Deref for Board {
+    type Target = Array2<i8>;
+
+    fn deref(&self) -> &Array2<i8> {
+        &self.cells
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Board) -> bool {
+        self.hash == other.hash && self.cells == other.cells
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+/// An m,n,k-game, a board of `row_count` by `column_count` where the first player to get
+/// `win_length` pieces in a row, column or diagonal wins. The default is normal 3x3 tic-tac-toe
+#[derive(Debug, Clone)]
 pub struct TicTacToe {
     pub row_count: usize,
     pub column_count: usize,
+    pub win_length: usize,
 }
 
 impl TicTacToe {
@@ -14,95 +406,722 @@ impl TicTacToe {
         TicTacToe {
             row_count: 3,
             column_count: 3,
+            win_length: 3,
         }
     }
 
-    pub fn check_win(&self, state: &Array2<i8>, player: i8) -> bool {
-        let state = state.clone();
-        let summed_rows = state.sum_axis(Axis(0));
-        let summed_collumns = state.sum_axis(Axis(1));
-
-        let win_on_any_row: bool = summed_rows.iter().any(|&x| x == player * 3);
-        let win_on_any_col: bool = summed_collumns.iter().any(|&x| x == player * 3);
-
-        let mut diagonal_summed: i8 = 0;
-        let mut diagonal_inversed_summed: i8 = 0;
+    pub fn new(row_count: usize, column_count: usize, win_length: usize) -> Result<TicTacToe> {
+        if row_count == 0 || column_count == 0 {
+            bail!(
+                "The board needs at least 1 row and column, got: {}x{}",
+                row_count,
+                column_count
+            );
+        }
 
-        for (i, row) in state.axis_iter(Axis(0)).enumerate() {
-            let row_vec = row.to_vec();
-            diagonal_summed += row_vec[i];
-            diagonal_inversed_summed += row_vec[2 - i];
+        if win_length == 0 || win_length > row_count.max(column_count) {
+            bail!(
+                "A win length of {} does not fit on a {}x{} board",
+                win_length,
+                row_count,
+                column_count
+            );
         }
 
-        let diagonal_win = diagonal_summed == (player * 3);
-        let diagonal_inversed_win = diagonal_inversed_summed == (player * 3);
+        Ok(TicTacToe {
+            row_count,
+            column_count,
+            win_length,
+        })
+    }
 
-        win_on_any_col || win_on_any_row || diagonal_win || diagonal_inversed_win
+    /// Checks if the given player has `win_length` pieces in a row on any row, column or diagonal
+    pub fn check_win(&self, state: &Array2<i8>, player: Player) -> bool {
+        has_pieces_in_a_row(state, player, self.win_length)
     }
 
     #[allow(dead_code)] // Used inside tests
-    pub fn create_state(&self, player_coordinates: Vec<(usize, usize, i8)>) -> Array2<i8> {
+    pub fn create_state(&self, player_coordinates: Vec<(usize, usize, Player)>) -> Board {
         let mut state = self.get_initial_state();
-        for coordinate_player in player_coordinates {
-            let row = coordinate_player.0;
-            let column = coordinate_player.1;
-            state = self.apply_move(&state, coordinate_player.2, (row, column));
+        for (row, column, player) in player_coordinates {
+            state = self.apply_move(&state, player, Move(row, column));
         }
 
         state
     }
 
-    pub fn get_initial_state(&self) -> Array2<i8> {
-        Array2::<i8>::zeros([self.row_count, self.column_count])
+    pub fn get_initial_state(&self) -> Board {
+        Board::empty(self.row_count, self.column_count)
     }
 
-    pub fn apply_move(&self, state: &Array2<i8>, player: i8, action: (usize, usize)) -> Array2<i8> {
+    pub fn apply_move(&self, state: &Board, player: Player, action: Move) -> Board {
         let mut next_state = state.clone();
+        next_state.place(action.into(), player);
+        next_state
+    }
 
-        let row = action.0;
-        let column = action.1;
+    /// Applies the move like `apply_move`, but explains why the move can not be played instead of
+    /// overwriting a piece or panicking
+    pub fn try_apply_move(&self, state: &Board, player: Player, action: Move) -> Result<Board> {
+        check_turn(self, state, player, Player::to_move_on(state))?;
 
-        next_state[[row, column]] = player;
-        next_state
+        let Move(row, column) = action;
+        if row >= self.row_count || column >= self.column_count {
+            bail!(
+                "Cell {:?} is outside of the {}x{} board",
+                action,
+                self.row_count,
+                self.column_count
+            );
+        }
+        if let Some(owner) = Player::from_cell(state[[row, column]]) {
+            bail!("Cell {:?} is already taken by {}", action, owner);
+        }
+
+        Ok(self.apply_move(state, player, action))
     }
 
-    pub fn get_value_and_terminated(&self, state: &Array2<i8>, player: i8) -> (f32, bool) {
-        // win
+    pub fn get_outcome(&self, state: &Board, player: Player) -> Outcome<Player> {
         if self.check_win(state, player) {
-            return (1.0, true);
+            return Outcome::Win(player);
         }
 
-        // draw
         if self.get_legal_moves(state).is_empty() {
-            return (0.5, true);
+            return Outcome::Draw;
         }
 
-        // lose
-        (0.0, false)
+        Outcome::Ongoing
     }
 
-    pub fn get_legal_moves(&self, state: &Array2<i8>) -> Vec<(usize, usize)> {
+    pub fn get_legal_moves(&self, state: &Array2<i8>) -> Vec<Move> {
         let legal_moves_as_mask: Array2<bool> = state.clone().map(|&x| x == 0);
         if !legal_moves_as_mask.iter().any(|&x| x) {
             return vec![];
         }
         legal_moves_as_mask
             .indexed_iter()
-            .filter_map(|(idx, &val)| if val { Some((idx.0, idx.1)) } else { None })
+            .filter_map(|(idx, &val)| if val { Some(Move(idx.0, idx.1)) } else { None })
             .collect()
     }
 
     pub fn print_state(&self, state: &Array2<i8>) -> Result<()> {
-        let data = state.clone();
-        let slice: &[i8] = data
-            .as_slice()
-            .ok_or(anyhow!("Unable to slice this array: {:?}", state))?;
-        let [rows, cols] = [data.shape()[0], data.shape()[1]];
+        print_board(state)
+    }
+}
+
+impl Game for TicTacToe {
+    type State = Board;
+    type Action = Move;
+    type Player = Player;
+
+    fn initial_state(&self) -> Board {
+        self.get_initial_state()
+    }
+
+    fn legal_actions(&self, state: &Board) -> Vec<Move> {
+        self.get_legal_moves(state)
+    }
+
+    fn apply(&self, state: &Board, player: Player, action: Move) -> Board {
+        self.apply_move(state, player, action)
+    }
+
+    fn outcome(&self, state: &Board, player: Player) -> Outcome<Player> {
+        self.get_outcome(state, player)
+    }
+
+    fn opponent(&self, player: Player) -> Player {
+        player.opponent()
+    }
 
+    fn state_hash(&self, state: &Board) -> u64 {
+        state.zobrist_hash()
+    }
+
+    fn evaluate(&self, state: &Board, player: Player) -> f32 {
+        open_lines_value(state, player, self.win_length)
+    }
+
+    fn distinct_actions(&self, state: &Board, actions: Vec<Move>) -> Vec<Move> {
+        let symmetries = Symmetry::of_board(self.row_count, self.column_count);
+        distinct_moves(state, &symmetries, actions)
+    }
+}
+
+/// The state of a `BitboardTicTacToe` game, cell (row, column) is bit `row * column_count + column`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BitboardState {
+    /// The cells of player 1 and of player -1
+    pub pieces: [u64; 2],
+    /// The Zobrist hash of the pieces, the same as that of a `Board` with the same pieces
+    pub hash: u64,
+}
+
+impl BitboardState {
+    fn occupied(&self) -> u64 {
+        self.pieces[0] | self.pieces[1]
+    }
+}
+
+/// The index of the player's pieces in a `BitboardState`
+fn piece_index(player: Player) -> usize {
+    usize::from(player == Player::O)
+}
+
+/// The same m,n,k-game as `TicTacToe`, with the same actions in the same order, but every player's
+/// pieces are a bitmask and wins are checked against precomputed masks of every line, so moves
+/// never allocate a board. Boards are limited to 64 cells
+#[derive(Debug, Clone)]
+pub struct BitboardTicTacToe {
+    /// The dimensions and win length of the board
+    board: TicTacToe,
+    /// Every line of `win_length` cells on the board, a player holding all cells of one has won
+    win_masks: Vec<u64>,
+    /// Every cell of the board
+    full_mask: u64,
+}
+
+impl BitboardTicTacToe {
+    #[allow(dead_code)] // Used inside tests
+    pub fn init() -> BitboardTicTacToe {
+        BitboardTicTacToe::from_board(TicTacToe::init())
+    }
+
+    pub fn new(
+        row_count: usize,
+        column_count: usize,
+        win_length: usize,
+    ) -> Result<BitboardTicTacToe> {
+        let board = TicTacToe::new(row_count, column_count, win_length)?;
+        if row_count * column_count > 64 {
+            bail!(
+                "A bitboard holds at most 64 cells, a {}x{} board has {}",
+                row_count,
+                column_count,
+                row_count * column_count
+            );
+        }
+
+        Ok(BitboardTicTacToe::from_board(board))
+    }
+
+    fn from_board(board: TicTacToe) -> BitboardTicTacToe {
+        let (row_count, column_count) = (board.row_count, board.column_count);
+        let directions: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+        let mut win_masks = vec![];
+        for row in 0..row_count {
+            for column in 0..column_count {
+                for &(row_step, column_step) in &directions {
+                    let mask: Option<u64> = (0..board.win_length as isize)
+                        .map(|step| {
+                            let next_row = row
+                                .checked_add_signed(row_step * step)
+                                .filter(|&next_row| next_row < row_count)?;
+                            let next_column = column
+                                .checked_add_signed(column_step * step)
+                                .filter(|&next_column| next_column < column_count)?;
+                            Some(1 << (next_row * column_count + next_column))
+                        })
+                        .sum();
+                    win_masks.extend(mask);
+                }
+            }
+        }
+
+        BitboardTicTacToe {
+            full_mask: u64::MAX >> (64 - row_count * column_count),
+            board,
+            win_masks,
+        }
+    }
+
+    /// The dimensions and win length of the board
+    pub fn board(&self) -> &TicTacToe {
+        &self.board
+    }
+
+    fn cell_mask(&self, (row, column): (usize, usize)) -> u64 {
+        1 << (row * self.board.column_count + column)
+    }
+
+    pub fn check_win(&self, state: &BitboardState, player: Player) -> bool {
+        let pieces = state.pieces[piece_index(player)];
+        self.win_masks.iter().any(|&mask| mask & !pieces == 0)
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn create_state(&self, player_coordinates: Vec<(usize, usize, Player)>) -> BitboardState {
+        let mut state = self.get_initial_state();
+        for (row, column, player) in player_coordinates {
+            state = self.apply_move(&state, player, Move(row, column));
+        }
+
+        state
+    }
+
+    pub fn get_initial_state(&self) -> BitboardState {
+        BitboardState::default()
+    }
+
+    pub fn apply_move(&self, state: &BitboardState, player: Player, action: Move) -> BitboardState {
+        let Move(row, column) = action;
+        let mut next_state = *state;
+        next_state.pieces[piece_index(player)] |= self.cell_mask(action.into());
+        next_state.hash ^= zobrist_key(row * self.board.column_count + column, player);
+        next_state
+    }
+
+    /// Applies the move like `apply_move`, but explains why the move can not be played instead of
+    /// playing on a taken cell or outside of the board
+    pub fn try_apply_move(
+        &self,
+        state: &BitboardState,
+        player: Player,
+        action: Move,
+    ) -> Result<BitboardState> {
+        let [x_pieces, o_pieces] = state.pieces.map(|pieces| pieces.count_ones() as usize);
+        check_turn(self, state, player, Player::to_move(x_pieces, o_pieces))?;
+
+        let Move(row, column) = action;
+        let board = &self.board;
+        if row >= board.row_count || column >= board.column_count {
+            bail!(
+                "Cell {:?} is outside of the {}x{} board",
+                action,
+                board.row_count,
+                board.column_count
+            );
+        }
+        let cell = self.cell_mask(action.into());
+        if let Some(owner) = [Player::X, Player::O]
+            .into_iter()
+            .find(|&owner| state.pieces[piece_index(owner)] & cell != 0)
+        {
+            bail!("Cell {:?} is already taken by {}", action, owner);
+        }
+
+        Ok(self.apply_move(state, player, action))
+    }
+
+    pub fn get_outcome(&self, state: &BitboardState, player: Player) -> Outcome<Player> {
+        if self.check_win(state, player) {
+            return Outcome::Win(player);
+        }
+
+        if state.occupied() == self.full_mask {
+            return Outcome::Draw;
+        }
+
+        Outcome::Ongoing
+    }
+
+    /// The empty cells, row by row like `TicTacToe`
+    pub fn get_legal_moves(&self, state: &BitboardState) -> Vec<Move> {
+        let mut empty_cells = !state.occupied() & self.full_mask;
+        let mut legal_moves = Vec::with_capacity(empty_cells.count_ones() as usize);
+        while empty_cells != 0 {
+            let cell = empty_cells.trailing_zeros() as usize;
+            legal_moves.push(Move(
+                cell / self.board.column_count,
+                cell % self.board.column_count,
+            ));
+            // Clears the lowest set bit
+            empty_cells &= empty_cells - 1;
+        }
+
+        legal_moves
+    }
+
+    /// The state as the board of `TicTacToe`
+    pub fn to_board(&self, state: &BitboardState) -> Array2<i8> {
+        Array2::from_shape_fn(
+            (self.board.row_count, self.board.column_count),
+            |position| {
+                let cell = self.cell_mask(position);
+                if state.pieces[0] & cell != 0 {
+                    1
+                } else if state.pieces[1] & cell != 0 {
+                    -1
+                } else {
+                    0
+                }
+            },
+        )
+    }
+
+    pub fn print_state(&self, state: &BitboardState) -> Result<()> {
+        print_board(&self.to_board(state))
+    }
+}
+
+impl Game for BitboardTicTacToe {
+    type State = BitboardState;
+    type Action = Move;
+    type Player = Player;
+
+    fn initial_state(&self) -> BitboardState {
+        self.get_initial_state()
+    }
+
+    fn legal_actions(&self, state: &BitboardState) -> Vec<Move> {
+        self.get_legal_moves(state)
+    }
+
+    fn apply(&self, state: &BitboardState, player: Player, action: Move) -> BitboardState {
+        self.apply_move(state, player, action)
+    }
+
+    fn outcome(&self, state: &BitboardState, player: Player) -> Outcome<Player> {
+        self.get_outcome(state, player)
+    }
+
+    fn opponent(&self, player: Player) -> Player {
+        player.opponent()
+    }
+
+    fn state_hash(&self, state: &BitboardState) -> u64 {
+        state.hash
+    }
+
+    /// The same value as `TicTacToe` gives, the win masks are the lines it counts
+    fn evaluate(&self, state: &BitboardState, player: Player) -> f32 {
+        let own = state.pieces[piece_index(player)];
+        let opponent = state.pieces[piece_index(player.opponent())];
+        let (mut own_pieces, mut opponent_pieces) = (0, 0);
+
+        for &mask in &self.win_masks {
+            let own_count = (own & mask).count_ones();
+            let opponent_count = (opponent & mask).count_ones();
+            if opponent_count == 0 {
+                own_pieces += own_count;
+            } else if own_count == 0 {
+                opponent_pieces += opponent_count;
+            }
+        }
+
+        if self.win_masks.is_empty() {
+            0.0
+        } else {
+            (own_pieces as f32 - opponent_pieces as f32)
+                / (self.win_masks.len() * self.board.win_length) as f32
+        }
+    }
+
+    fn distinct_actions(&self, state: &BitboardState, actions: Vec<Move>) -> Vec<Move> {
+        let symmetries = Symmetry::of_board(self.board.row_count, self.board.column_count);
+        distinct_moves(&self.to_board(state), &symmetries, actions)
+    }
+}
+
+/// Connect Four, players drop their pieces into one of the columns, where it falls down to the
+/// lowest free row. The first player to get 4 pieces in a row, column or diagonal wins
+#[derive(Debug, Clone)]
+pub struct ConnectFour {
+    pub row_count: usize,
+    pub column_count: usize,
+    pub win_length: usize,
+}
+
+impl ConnectFour {
+    pub fn init() -> ConnectFour {
+        ConnectFour {
+            row_count: 6,
+            column_count: 7,
+            win_length: 4,
+        }
+    }
+
+    pub fn check_win(&self, state: &Array2<i8>, player: Player) -> bool {
+        has_pieces_in_a_row(state, player, self.win_length)
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn create_state(&self, player_columns: Vec<(usize, Player)>) -> Board {
+        let mut state = self.get_initial_state();
+        for (column, player) in player_columns {
+            state = self.apply_move(&state, player, column);
+        }
+
+        state
+    }
+
+    pub fn get_initial_state(&self) -> Board {
+        Board::empty(self.row_count, self.column_count)
+    }
+
+    /// Drops the player's piece into the given column, it lands on the lowest empty row
+    pub fn apply_move(&self, state: &Board, player: Player, column: usize) -> Board {
+        let mut next_state = state.clone();
+
+        let row = (0..self.row_count)
+            .rev()
+            .find(|&row| state[[row, column]] == 0);
+
+        if let Some(row) = row {
+            next_state.place((row, column), player);
+        }
+        next_state
+    }
+
+    /// Applies the move like `apply_move`, but explains why the move can not be played instead of
+    /// ignoring a full column or panicking
+    pub fn try_apply_move(&self, state: &Board, player: Player, column: usize) -> Result<Board> {
+        check_turn(self, state, player, Player::to_move_on(state))?;
+
+        if column >= self.column_count {
+            bail!(
+                "Column {} is outside of the board, which has {} columns",
+                column,
+                self.column_count
+            );
+        }
+        if state[[0, column]] != 0 {
+            bail!("Column {} is already full", column);
+        }
+
+        Ok(self.apply_move(state, player, column))
+    }
+
+    pub fn get_outcome(&self, state: &Board, player: Player) -> Outcome<Player> {
+        if self.check_win(state, player) {
+            return Outcome::Win(player);
+        }
+
+        if self.get_legal_moves(state).is_empty() {
+            return Outcome::Draw;
+        }
+
+        Outcome::Ongoing
+    }
+
+    /// A column is a legal move as long as its top row is still empty
+    pub fn get_legal_moves(&self, state: &Array2<i8>) -> Vec<usize> {
+        (0..self.column_count)
+            .filter(|&column| state[[0, column]] == 0)
+            .collect()
+    }
+
+    pub fn print_state(&self, state: &Array2<i8>) -> Result<()> {
+        print_board(state)?;
+        let column_numbers: Vec<String> = (0..self.column_count)
+            .map(|column| column.to_string())
+            .collect();
+        println!("{}", column_numbers.join("  "));
+        println!();
+        Ok(())
+    }
+}
+
+impl Game for ConnectFour {
+    type State = Board;
+    type Action = usize;
+    type Player = Player;
+
+    fn initial_state(&self) -> Board {
+        self.get_initial_state()
+    }
+
+    fn legal_actions(&self, state: &Board) -> Vec<usize> {
+        self.get_legal_moves(state)
+    }
+
+    fn apply(&self, state: &Board, player: Player, action: usize) -> Board {
+        self.apply_move(state, player, action)
+    }
+
+    fn outcome(&self, state: &Board, player: Player) -> Outcome<Player> {
+        self.get_outcome(state, player)
+    }
+
+    fn opponent(&self, player: Player) -> Player {
+        player.opponent()
+    }
+
+    fn state_hash(&self, state: &Board) -> u64 {
+        state.zobrist_hash()
+    }
+
+    fn evaluate(&self, state: &Board, player: Player) -> f32 {
+        open_lines_value(state, player, self.win_length)
+    }
+
+    /// Gravity rules out every symmetry except mirroring the columns
+    fn distinct_actions(&self, state: &Board, actions: Vec<usize>) -> Vec<usize> {
+        let positions = actions.into_iter().map(|column| (0, column)).collect();
+        symmetry::distinct_positions(state, &[Symmetry::FlipColumns], positions)
+            .into_iter()
+            .map(|(_, column)| column)
+            .collect()
+    }
+}
+
+/// The state of an ultimate tic-tac-toe game
+#[derive(Debug, Clone, PartialEq, Hash, Serialize)]
+pub struct UltimateState {
+    /// All 9x9 cells, sub-board (i, j) covers rows 3i..3i+3 and columns 3j..3j+3
+    pub board: Board,
+    /// The 3x3 board of sub-boards, holding the player that won each sub-board
+    pub meta_board: Array2<i8>,
+    /// The sub-board the next player has to play in, None when any open sub-board may be played
+    pub next_sub_board: Option<(usize, usize)>,
+}
+
+/// Ultimate tic-tac-toe, 9 tic-tac-toe sub-boards making up one big 3x3 meta-board.
+/// The cell played within a sub-board dictates the sub-board the opponent has to play in next,
+/// winning a sub-board claims that cell of the meta-board and winning the meta-board wins the game
+#[derive(Debug, Clone)]
+pub struct UltimateTicTacToe {
+    /// Used for the win logic of both the sub-boards and the meta-board
+    sub_game: TicTacToe,
+}
+
+impl UltimateTicTacToe {
+    pub fn init() -> UltimateTicTacToe {
+        UltimateTicTacToe {
+            sub_game: TicTacToe::init(),
+        }
+    }
+
+    pub fn check_win(&self, state: &UltimateState, player: Player) -> bool {
+        self.sub_game.check_win(&state.meta_board, player)
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn create_state(&self, player_coordinates: Vec<(usize, usize, Player)>) -> UltimateState {
+        let mut state = self.get_initial_state();
+        for (row, column, player) in player_coordinates {
+            state = self.apply_move(&state, player, Move(row, column));
+        }
+
+        state
+    }
+
+    pub fn get_initial_state(&self) -> UltimateState {
+        UltimateState {
+            board: Board::empty(9, 9),
+            meta_board: Array2::<i8>::zeros([3, 3]),
+            next_sub_board: None,
+        }
+    }
+
+    /// Plays the given cell of the 9x9 board, claims its sub-board if that is won and sends the
+    /// opponent to the sub-board matching the cell's position within its sub-board
+    pub fn apply_move(&self, state: &UltimateState, player: Player, action: Move) -> UltimateState {
+        let mut next_state = state.clone();
+
+        let Move(row, column) = action;
+        next_state.board.place((row, column), player);
+
+        let sub_board = (row / 3, column / 3);
+        if self
+            .sub_game
+            .check_win(&self.get_sub_board(&next_state, sub_board), player)
+        {
+            next_state.meta_board[[sub_board.0, sub_board.1]] = player.cell();
+        }
+
+        let next_sub_board = (row % 3, column % 3);
+        next_state.next_sub_board = if self.is_sub_board_open(&next_state, next_sub_board) {
+            Some(next_sub_board)
+        } else {
+            None
+        };
+        next_state
+    }
+
+    /// Applies the move like `apply_move`, but explains why the move can not be played instead of
+    /// playing on a taken cell, outside of the board or in a sub-board that can not be played
+    pub fn try_apply_move(
+        &self,
+        state: &UltimateState,
+        player: Player,
+        action: Move,
+    ) -> Result<UltimateState> {
+        check_turn(self, state, player, Player::to_move_on(&state.board))?;
+
+        let Move(row, column) = action;
+        if row >= 9 || column >= 9 {
+            bail!("Cell {:?} is outside of the 9x9 board", action);
+        }
+        if let Some(owner) = Player::from_cell(state.board[[row, column]]) {
+            bail!("Cell {:?} is already taken by {}", action, owner);
+        }
+        let sub_board = (row / 3, column / 3);
+        match state.next_sub_board {
+            Some(next_sub_board) if next_sub_board != sub_board => {
+                bail!(
+                    "The move has to be played in sub-board {:?}",
+                    next_sub_board
+                )
+            }
+            None if !self.is_sub_board_open(state, sub_board) => {
+                bail!("Sub-board {:?} is already decided", sub_board)
+            }
+            _ => {}
+        }
+
+        Ok(self.apply_move(state, player, action))
+    }
+
+    pub fn get_outcome(&self, state: &UltimateState, player: Player) -> Outcome<Player> {
+        if self.check_win(state, player) {
+            return Outcome::Win(player);
+        }
+
+        if self.get_legal_moves(state).is_empty() {
+            return Outcome::Draw;
+        }
+
+        Outcome::Ongoing
+    }
+
+    /// The empty cells of the sub-board that has to be played, or of every open sub-board
+    pub fn get_legal_moves(&self, state: &UltimateState) -> Vec<Move> {
+        let sub_boards: Vec<(usize, usize)> = match state.next_sub_board {
+            Some(sub_board) => vec![sub_board],
+            None => (0..3)
+                .flat_map(|row| (0..3).map(move |column| (row, column)))
+                .filter(|&sub_board| self.is_sub_board_open(state, sub_board))
+                .collect(),
+        };
+
+        sub_boards
+            .into_iter()
+            .flat_map(|(sub_row, sub_column)| {
+                self.sub_game
+                    .get_legal_moves(&self.get_sub_board(state, (sub_row, sub_column)))
+                    .into_iter()
+                    .map(move |Move(row, column)| Move(sub_row * 3 + row, sub_column * 3 + column))
+            })
+            .collect()
+    }
+
+    pub fn get_sub_board(&self, state: &UltimateState, sub_board: (usize, usize)) -> Array2<i8> {
+        let (row, column) = (sub_board.0 * 3, sub_board.1 * 3);
+        state
+            .board
+            .slice(s![row..row + 3, column..column + 3])
+            .to_owned()
+    }
+
+    /// A sub-board is open as long as it has not been won and still has an empty cell
+    fn is_sub_board_open(&self, state: &UltimateState, sub_board: (usize, usize)) -> bool {
+        state.meta_board[[sub_board.0, sub_board.1]] == 0
+            && self
+                .get_sub_board(state, sub_board)
+                .iter()
+                .any(|&cell| cell == 0)
+    }
+
+    pub fn print_state(&self, state: &UltimateState) -> Result<()> {
         println!();
-        for i in 0..rows {
-            for j in 0..cols {
-                let cell = slice[i * cols + j];
+        for row in 0..9 {
+            if row > 0 && row % 3 == 0 {
+                println!("--------+---------+--------");
+            }
+            for column in 0..9 {
+                let cell = state.board[[row, column]];
                 if cell > 0 {
                     print!("X");
                 } else if cell < 0 {
@@ -110,13 +1129,182 @@ impl TicTacToe {
                 } else {
                     print!("-");
                 }
-                if j + 1 < cols {
-                    print!("  ");
+                if column + 1 < 9 {
+                    print!("{}", if column % 3 == 2 { " | " } else { "  " });
                 }
             }
             println!();
         }
         println!();
+
+        match state.next_sub_board {
+            Some(sub_board) => println!("Next sub-board: {:?}", sub_board),
+            None => println!("Next sub-board: any open sub-board"),
+        }
         Ok(())
     }
 }
+
+impl Game for UltimateTicTacToe {
+    type State = UltimateState;
+    type Action = Move;
+    type Player = Player;
+
+    fn initial_state(&self) -> UltimateState {
+        self.get_initial_state()
+    }
+
+    fn legal_actions(&self, state: &UltimateState) -> Vec<Move> {
+        self.get_legal_moves(state)
+    }
+
+    fn apply(&self, state: &UltimateState, player: Player, action: Move) -> UltimateState {
+        self.apply_move(state, player, action)
+    }
+
+    fn outcome(&self, state: &UltimateState, player: Player) -> Outcome<Player> {
+        self.get_outcome(state, player)
+    }
+
+    fn opponent(&self, player: Player) -> Player {
+        player.opponent()
+    }
+
+    /// The won sub-boards follow from the board, but the sub-board that has to be played does
+    /// not, it is hashed like a piece on the cells after those of the board
+    fn state_hash(&self, state: &UltimateState) -> u64 {
+        let next_sub_board_key = state.next_sub_board.map_or(0, |(row, column)| {
+            zobrist_key(81 + row * 3 + column, Player::X)
+        });
+        state.board.zobrist_hash() ^ next_sub_board_key
+    }
+
+    /// Only the won sub-boards count, as lines on the meta-board
+    fn evaluate(&self, state: &UltimateState, player: Player) -> f32 {
+        open_lines_value(&state.meta_board, player, 3)
+    }
+}
+
+/// Checks that the game is not over yet and that it is the given player's turn
+fn check_turn<G: Game<Player = Player>>(
+    game: &G,
+    state: &G::State,
+    player: Player,
+    player_to_move: Player,
+) -> Result<()> {
+    if game.outcome(state, player_to_move.opponent()).is_over() {
+        bail!("The game is already over");
+    }
+    if player != player_to_move {
+        bail!("It is {}'s turn, not {}'s", player_to_move, player);
+    }
+
+    Ok(())
+}
+
+/// `symmetry::distinct_positions` for the cells of moves
+fn distinct_moves(state: &Array2<i8>, symmetries: &[Symmetry], moves: Vec<Move>) -> Vec<Move> {
+    let positions = moves.into_iter().map(Into::into).collect();
+    symmetry::distinct_positions(state, symmetries, positions)
+        .into_iter()
+        .map(Move::from)
+        .collect()
+}
+
+/// Checks if the given player has `length` pieces in a row on any row, column or diagonal
+fn has_pieces_in_a_row(state: &Array2<i8>, player: Player, length: usize) -> bool {
+    let player = player.cell();
+    // Right, down, down-right and down-left, the other directions are covered by starting
+    // from the other end of the line
+    let directions: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+    let length = length as isize;
+
+    state
+        .indexed_iter()
+        .filter(|(_, &cell)| cell == player)
+        .any(|((row, column), _)| {
+            directions.iter().any(|&(row_step, column_step)| {
+                (1..length).all(|step| {
+                    let next_row = row.checked_add_signed(row_step * step);
+                    let next_column = column.checked_add_signed(column_step * step);
+
+                    next_row
+                        .zip(next_column)
+                        .and_then(|position| state.get(position))
+                        == Some(&player)
+                })
+            })
+        })
+}
+
+/// Values the board for the given player by the lines of `length` cells that only one player has
+/// pieces on, as only those can still be won. Every piece on such a line counts for its player,
+/// the value is the difference between both players, divided by the most pieces the lines can
+/// hold so it stays between -1.0 and 1.0
+fn open_lines_value(state: &Array2<i8>, player: Player, length: usize) -> f32 {
+    let player = player.cell();
+    let directions: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+    let (mut own_pieces, mut opponent_pieces) = (0, 0);
+    let mut line_count = 0;
+
+    for ((row, column), _) in state.indexed_iter() {
+        for &(row_step, column_step) in &directions {
+            let line: Option<Vec<i8>> = (0..length as isize)
+                .map(|step| {
+                    let next_row = row.checked_add_signed(row_step * step)?;
+                    let next_column = column.checked_add_signed(column_step * step)?;
+                    state.get((next_row, next_column)).copied()
+                })
+                .collect();
+            let Some(line) = line else {
+                continue;
+            };
+            line_count += 1;
+
+            let own = line.iter().filter(|&&cell| cell == player).count();
+            let opponent = line
+                .iter()
+                .filter(|&&cell| cell != 0 && cell != player)
+                .count();
+            if opponent == 0 {
+                own_pieces += own;
+            } else if own == 0 {
+                opponent_pieces += opponent;
+            }
+        }
+    }
+
+    if line_count == 0 {
+        0.0
+    } else {
+        (own_pieces as f32 - opponent_pieces as f32) / (line_count * length) as f32
+    }
+}
+
+fn print_board(state: &Array2<i8>) -> Result<()> {
+    let data = state.clone();
+    let slice: &[i8] = data
+        .as_slice()
+        .ok_or(anyhow!("Unable to slice this array: {:?}", state))?;
+    let [rows, cols] = [data.shape()[0], data.shape()[1]];
+
+    println!();
+    for i in 0..rows {
+        for j in 0..cols {
+            let cell = slice[i * cols + j];
+            if cell > 0 {
+                print!("X");
+            } else if cell < 0 {
+                print!("O");
+            } else {
+                print!("-");
+            }
+            if j + 1 < cols {
+                print!("  ");
+            }
+        }
+        println!();
+    }
+    println!();
+    Ok(())
+}
@@ -1,12 +1,33 @@
+use anyhow::Result;
+
+use crate::config::MctsConfig;
 use crate::play_interface::choose_play_option;
 
+mod arena;
+mod config;
+mod final_move;
 mod games;
 mod mcts;
+mod parallel;
 mod play_interface;
+mod rollout;
+mod selection;
+mod solver;
+mod symmetry;
 mod tests;
 
 fn main() {
-    if let Err(error) = choose_play_option() {
+    if let Err(error) = run() {
         eprintln!("Error: {error:?}");
     }
 }
+
+/// Starts the game, using the MCTS config file given as the first argument if there is one
+fn run() -> Result<()> {
+    let config = match std::env::args().nth(1) {
+        Some(path) => MctsConfig::from_file(path)?,
+        None => MctsConfig::default(),
+    };
+
+    choose_play_option(&config)
+}
@@ -1,245 +1,1191 @@
-use ndarray::Array2;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::f32;
+use std::fmt::{self, Debug, Display, Formatter, Write};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard};
+use std::thread;
+use std::time::Instant;
 
-use crate::games::TicTacToe;
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use serde_json::json;
 
-struct Node {
-    state: Array2<i8>,
-    player: i8,
-    legal_moves: Vec<(usize, usize)>,
-    action_taken: Option<(usize, usize)>,
+use crate::{
+    arena::Arena,
+    config::{FinalMove, MctsConfig, Parallelism},
+    final_move,
+    games::{Game, Position},
+    rollout::{self, RolloutPolicy},
+    selection::{self, ucb, ChildStatistics, SelectionPolicy},
+};
+
+struct Node<G: Game> {
+    position: Position<G>,
+    /// The legal moves that have not been expanded into a child yet
+    legal_moves: Mutex<Vec<G::Action>>,
 
     index: usize,
+    /// The parent that created this node, with transpositions other parents can reach it too
     parent_index: Option<usize>,
-    children_indices: Vec<usize>,
+    /// Locked per node, so that expanding a node only waits for the workers reading that node
+    children: RwLock<Vec<Edge<G>>>,
 
-    visit_count: u32,
-    value_sum: f32,
+    visit_count: AtomicU32,
+    value_sum: AtomicF32,
+    /// The sum of the squared values, for the variance used by UCB1-Tuned
+    value_squared_sum: AtomicF32,
+    /// The number of searches currently passing through this node, these count as losses when
+    /// selecting so that the other threads of a tree-parallel search are steered to other paths
+    virtual_loss: AtomicU32,
+    /// The `Proof` of this node, stored as its discriminant. Without the solver only finished games
+    /// are proven
+    proof: AtomicU8,
 }
 
-impl Node {
+impl<G: Game> Node<G> {
     pub fn new(
-        state: Array2<i8>,
-        player: i8,
-        legal_moves: Vec<(usize, usize)>,
-        action_taken: Option<(usize, usize)>,
+        position: Position<G>,
+        legal_moves: Vec<G::Action>,
         index: usize,
         parent_index: Option<usize>,
     ) -> Self {
         Self {
-            state,
-            player,
-            legal_moves,
-            action_taken,
+            position,
+            legal_moves: Mutex::new(legal_moves),
             index,
             parent_index,
-            children_indices: vec![],
-            visit_count: 0,
-            value_sum: 0.0,
+            children: RwLock::default(),
+            visit_count: AtomicU32::new(0),
+            value_sum: AtomicF32::default(),
+            value_squared_sum: AtomicF32::default(),
+            virtual_loss: AtomicU32::new(0),
+            proof: AtomicU8::new(Proof::Unproven as u8),
+        }
+    }
+
+    fn visit_count(&self) -> u32 {
+        self.visit_count.load(Ordering::Relaxed)
+    }
+
+    /// The average value of this node, 0.0 when it has not been visited yet
+    fn mean_value(&self) -> f32 {
+        let visit_count = self.visit_count();
+        if visit_count > 0 {
+            self.value_sum.load() / visit_count as f32
+        } else {
+            0.0
+        }
+    }
+
+    fn proof(&self) -> Proof {
+        match self.proof.load(Ordering::Relaxed) {
+            1 => Proof::Win,
+            2 => Proof::Loss,
+            3 => Proof::Draw,
+            _ => Proof::Unproven,
         }
     }
 
+    fn set_proof(&self, proof: Proof) {
+        self.proof.store(proof as u8, Ordering::Relaxed);
+    }
+
+    fn legal_moves(&self) -> MutexGuard<'_, Vec<G::Action>> {
+        self.legal_moves
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn legal_moves_mut(&mut self) -> &mut Vec<G::Action> {
+        self.legal_moves
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn children(&self) -> RwLockReadGuard<'_, Vec<Edge<G>>> {
+        self.children.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn children_mut(&mut self) -> &mut Vec<Edge<G>> {
+        self.children
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Checks if this node has been fully expanded, by checking that there are no more legal moves
     /// and that there are children present
     fn is_fully_expanded(&self) -> bool {
-        self.legal_moves.is_empty() && !self.children_indices.is_empty()
+        self.legal_moves().is_empty() && !self.children().is_empty()
     }
 }
 
-pub struct Mcts<'a> {
-    args: HashMap<&'a str, f32>,
-    game: TicTacToe,
-    tree: Vec<Node>,
+/// The game theoretic value of a node proven by the MCTS-Solver, from the perspective of the
+/// player that moved into the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Proof {
+    #[default]
+    Unproven = 0,
+    Win = 1,
+    Loss = 2,
+    Draw = 3,
 }
 
-impl<'a> Mcts<'a> {
-    pub fn new(
-        args: HashMap<&'a str, f32>,
-        game: TicTacToe,
-        root_state: &Array2<i8>,
-        player: i8,
-    ) -> Mcts<'a> {
-        let root = Node::new(
-            root_state.clone(),
-            player,
-            game.get_legal_moves(root_state),
-            None,
+impl Proof {
+    /// The proof of a finished game with the given reward
+    fn from_reward(reward: f32) -> Proof {
+        if reward > 0.0 {
+            Proof::Win
+        } else if reward < 0.0 {
+            Proof::Loss
+        } else {
+            Proof::Draw
+        }
+    }
+
+    /// The value a proven node is backpropagated with, None when it is not proven
+    fn value(self) -> Option<f32> {
+        match self {
+            Proof::Unproven => None,
+            Proof::Win => Some(1.0),
+            Proof::Loss => Some(-1.0),
+            Proof::Draw => Some(0.0),
+        }
+    }
+}
+
+/// The move from a node to one of its children. With transpositions a node can be the child of
+/// several parents, each reaching it with their own action
+struct Edge<G: Game> {
+    action: G::Action,
+    child_index: usize,
+
+    /// The all-moves-as-first statistics of the action, counting every search through the parent
+    /// where the action was played later on by the same player, only kept when RAVE is used
+    rave_visit_count: AtomicU32,
+    rave_value_sum: AtomicF32,
+}
+
+impl<G: Game> Edge<G> {
+    fn new(action: G::Action, child_index: usize) -> Self {
+        Self {
+            action,
+            child_index,
+            rave_visit_count: AtomicU32::new(0),
+            rave_value_sum: AtomicF32::default(),
+        }
+    }
+}
+
+/// The key of a position in the transposition table, only a hint as different positions can
+/// share a key. The state is keyed by its Zobrist hash, so the board is never hashed itself
+fn position_hash<G: Game>(game: &G, position: &Position<G>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    game.state_hash(position.state()).hash(&mut hasher);
+    position.to_move().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// An f32 that can be added to from multiple threads at once, stored as its bits
+#[derive(Default)]
+struct AtomicF32(AtomicU32);
+
+impl AtomicF32 {
+    fn load(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn fetch_add(&self, value: f32) {
+        // The closure always returns Some, so the update can not fail
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f32::from_bits(bits) + value).to_bits())
+            });
+    }
+}
+
+/// The statistics the search gathered for one of the root's actions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionStatistics<A> {
+    pub action: A,
+    pub visit_count: u32,
+    /// The average value of the action, from the perspective of the player taking it
+    pub mean_value: f32,
+    pub ucb: f32,
+    /// The probability of the action being chosen before searching, uniform over the legal moves
+    pub prior: f32,
+    /// Whether the action is proven to win, lose or draw. Without the solver only actions that end
+    /// the game are proven
+    pub proof: Proof,
+}
+
+/// A snapshot of what the search has found so far
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult<A> {
+    /// The statistics of every expanded root action, most visited first
+    pub actions: Vec<ActionStatistics<A>>,
+    /// The line of play the search expects, following the most visited child from the root
+    pub principal_variation: Vec<A>,
+    pub node_count: usize,
+    /// The depth of the deepest node, the root has depth 0
+    pub max_depth: usize,
+}
+
+impl<A> SearchResult<A> {
+    pub fn best_action(&self) -> Option<&ActionStatistics<A>> {
+        self.actions.first()
+    }
+}
+
+impl<A: Debug> Display for SearchResult<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} nodes, {} deep, principal variation: {:?}",
+            self.node_count, self.max_depth, self.principal_variation
+        )?;
+        for statistics in &self.actions {
+            writeln!(
+                f,
+                "{:?}: {} visits, mean value {:.3}, UCB {:.3}, prior {:.3}",
+                statistics.action,
+                statistics.visit_count,
+                statistics.mean_value,
+                statistics.ucb,
+                statistics.prior
+            )?;
+            if statistics.proof != Proof::Unproven {
+                writeln!(f, "    proven {:?}", statistics.proof)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Limits which nodes of the tree are exported, a node is only exported when its parent is
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExportOptions {
+    /// The depth of the deepest nodes to export, the root has depth 0
+    pub max_depth: Option<usize>,
+    /// The number of visits a node needs to be exported, the root is always exported
+    pub min_visits: u32,
+}
+
+pub struct Mcts<G: Game> {
+    config: MctsConfig,
+    game: G,
+    tree: Vec<Node<G>>,
+    /// The index of the node of every position in the tree, None when transpositions are off
+    transpositions: Option<HashMap<u64, usize>>,
+    rollout_policy: Box<dyn RolloutPolicy<G>>,
+    selection_policy: Box<dyn SelectionPolicy>,
+    stop_requested: Arc<AtomicBool>,
+    rng: StdRng,
+    /// The seed the rng was created with, None when the rng was given directly
+    seed: Option<u64>,
+}
+
+/// Stops a running search from another thread, the search then returns the best action it has
+/// found so far. A stop requested while no search is running stops the next search right after
+/// its first iteration
+#[allow(dead_code)] // Used inside tests
+#[derive(Clone)]
+pub struct StopHandle {
+    stop_requested: Arc<AtomicBool>,
+}
+
+#[allow(dead_code)] // Used inside tests
+impl StopHandle {
+    pub fn stop(&self) {
+        self.stop_requested.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<G: Game> Mcts<G> {
+    /// Creates a search that uses the seed of the config, or a random seed when the config has
+    /// none. Searches with the same seed and limits other than time build identical trees
+    pub fn new(config: MctsConfig, game: G, root: &Position<G>) -> Mcts<G> {
+        let seed = config.seed().unwrap_or_else(rand::random);
+        let mut mcts = Self::with_rng(config, game, root, StdRng::seed_from_u64(seed));
+        mcts.seed = Some(seed);
+        mcts
+    }
+
+    /// Creates a search that uses the given rng for all of its random choices
+    pub fn with_rng(config: MctsConfig, game: G, root: &Position<G>, rng: StdRng) -> Mcts<G> {
+        // The tree remembers the moves from its root itself, so the root drops its history
+        let root_node = Node::new(
+            Position::new(&game, root.state().clone(), root.to_move()),
+            game.legal_actions(root.state()),
             0,
             None,
         );
-        Mcts {
-            args,
+        let mut mcts = Mcts {
+            rollout_policy: rollout::from_config(config.rollout()),
+            selection_policy: selection::from_config(
+                config.selection(),
+                config.exploration_constant(),
+            ),
+            config,
             game,
-            tree: vec![root],
+            tree: vec![root_node],
+            transpositions: None,
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            rng,
+            seed: None,
+        };
+        mcts.rebuild_transpositions();
+        mcts.merge_symmetric_root_moves();
+        mcts
+    }
+
+    /// The seed of the rng, needed to replay this search
+    #[allow(dead_code)] // Used inside tests
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Searches until any of the limits of the config is reached or a stop is requested, a
+    /// max-robust search then keeps searching until its final move is robust
+    #[allow(dead_code)] // Used inside tests
+    pub fn search(&mut self) -> G::Action {
+        self.run_workers(Limits::Config);
+        if let FinalMove::MaxRobust { max_extra_searches } = self.config.final_move() {
+            final_move::search_until_robust(
+                self,
+                max_extra_searches,
+                Self::action_statistics,
+                Self::search_more,
+            );
         }
+        self.get_best_action()
     }
 
-    pub fn search(&mut self) -> (usize, usize) {
-        for _ in 0..self.args["num_searches"] as u32 {
-            let mut node_index = self.select(0);
-            let node = &self.tree[node_index];
+    /// Anytime search, ignores the limits of the config and only stops once a stop is requested
+    /// through the `StopHandle`
+    #[allow(dead_code)] // Used inside tests
+    pub fn search_until_stopped(&mut self) -> G::Action {
+        self.run_workers(Limits::StopRequest);
+        self.get_best_action()
+    }
+
+    /// Adds the given number of searches to the tree, ignoring the limits of the config
+    pub fn search_more(&mut self, num_searches: u32) {
+        self.run_workers(Limits::Searches(num_searches));
+    }
+
+    /// Searches until any of the limits of the config is reached or a stop is requested, without
+    /// choosing a move
+    pub fn search_until_limits(&mut self) {
+        self.run_workers(Limits::Config);
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle {
+            stop_requested: Arc::clone(&self.stop_requested),
+        }
+    }
+
+    /// Searches the tree until the workers stop. With tree parallelization every thread of the
+    /// config runs a worker on the same tree, otherwise a single worker runs on this thread
+    fn run_workers(&mut self, limits: Limits) {
+        let num_workers = match self.config.parallelism() {
+            Parallelism::Tree => self.config.num_threads(),
+            Parallelism::Root => 1,
+        };
+        let Mcts {
+            config,
+            game,
+            tree,
+            transpositions,
+            rollout_policy,
+            selection_policy,
+            stop_requested,
+            rng,
+            ..
+        } = self;
 
-            let (mut value, terminated) =
-                self.game.get_value_and_terminated(&node.state, node.player);
+        // The workers add nodes to an arena that never moves them, so reading a node or adding one
+        // never locks the whole tree
+        let shared_tree = Arena::from(std::mem::take(tree));
+        let shared_transpositions = transpositions.take().map(Mutex::new);
+        let worker = Worker {
+            config,
+            game,
+            tree: &shared_tree,
+            transpositions: shared_transpositions.as_ref(),
+            rollout_policy: rollout_policy.as_ref(),
+            selection_policy: selection_policy.as_ref(),
+            stop_requested,
+            limits,
+            start: Instant::now(),
+            num_searches: AtomicU32::new(0),
+        };
+
+        // The extra workers get their own rng, seeded by the rng of the search
+        let mut worker_rngs: Vec<StdRng> = (1..num_workers)
+            .map(|_| StdRng::seed_from_u64(rng.random()))
+            .collect();
+        thread::scope(|scope| {
+            let worker = &worker;
+            for worker_rng in &mut worker_rngs {
+                scope.spawn(move || worker.run(worker_rng));
+            }
+            worker.run(rng);
+        });
+
+        *tree = shared_tree.into_vec();
+        *transpositions = shared_transpositions.map(|shared_transpositions| {
+            shared_transpositions
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+        });
+        stop_requested.store(false, Ordering::Relaxed);
+    }
+
+    /// Moves the root to the child reached by the given action, which can be a move of either
+    /// player. The statistics of that child's subtree are kept, the rest of the tree is pruned
+    pub fn advance(&mut self, action: G::Action) {
+        let root = &self.tree[0];
+        let child_index = root
+            .children()
+            .iter()
+            .find(|edge| edge.action == action)
+            .map(|edge| edge.child_index);
 
-            if !terminated {
-                node_index = self.expand(node_index);
-                value = self.simulate(node_index);
+        match child_index {
+            Some(child_index) => self.tree = self.take_subtree(child_index),
+
+            // The action was never expanded, so there are no statistics to keep
+            None => {
+                let position = root.position.child(&self.game, action);
+                let legal_moves = self.game.legal_actions(position.state());
+                self.tree = vec![Node::new(position, legal_moves, 0, None)];
             }
+        }
+        self.rebuild_transpositions();
+        self.merge_symmetric_root_moves();
+    }
 
-            self.backpropagate(node_index, value);
+    /// Drops the root's legal moves that are symmetric to one of its children or to an earlier
+    /// legal move, if symmetric moves are merged
+    fn merge_symmetric_root_moves(&mut self) {
+        if !self.config.merge_symmetric_moves() {
+            return;
         }
 
-        self.get_best_action()
+        let root = &self.tree[0];
+        let actions = root
+            .children()
+            .iter()
+            .map(|edge| edge.action)
+            .chain(root.legal_moves().iter().copied())
+            .collect();
+        let distinct_actions = self.game.distinct_actions(root.position.state(), actions);
+
+        self.tree[0]
+            .legal_moves_mut()
+            .retain(|action| distinct_actions.contains(action));
     }
 
-    /// Loops through the given nodes children, if any, and returns the child with the best UCB value
-    #[allow(non_snake_case)]
-    fn select(&self, node_index: usize) -> usize {
-        let mut node = &self.tree[node_index];
-        while node.is_fully_expanded() {
-            let mut best_child_index = node.index;
-            let mut best_UCB = f32::MIN;
-            for child_index in &node.children_indices {
-                let child_index = *child_index;
-                let parent = node;
-                let child = &self.tree[child_index];
-                let ucb = self.calculate_UCB(parent, child);
-                if ucb > best_UCB {
-                    best_child_index = child_index;
-                    best_UCB = ucb;
+    /// Fills the transposition table with the nodes of the tree, if transpositions are used
+    fn rebuild_transpositions(&mut self) {
+        self.transpositions = self.config.transpositions().then(|| {
+            self.tree
+                .iter()
+                .map(|node| (position_hash(&self.game, &node.position), node.index))
+                .collect()
+        });
+    }
+
+    /// Gathers the statistics of the tree as it is now, can be called after or between searches
+    pub fn search_result(&self) -> SearchResult<G::Action> {
+        let root = &self.tree[0];
+        let actions = self.action_statistics();
+
+        let mut principal_variation = vec![];
+        let mut node = root;
+        while let Some((action, child_index)) = self.most_visited_child(node) {
+            principal_variation.push(action);
+            node = &self.tree[child_index];
+        }
+
+        SearchResult {
+            actions,
+            principal_variation,
+            node_count: self.tree.len(),
+            max_depth: self.node_depths().into_iter().max().unwrap_or(0),
+        }
+    }
+
+    /// The statistics of every expanded root action, most visited first
+    fn action_statistics(&self) -> Vec<ActionStatistics<G::Action>> {
+        let root = &self.tree[0];
+        let num_legal_moves = root.children().len() + root.legal_moves().len();
+
+        let mut actions: Vec<ActionStatistics<G::Action>> = root
+            .children()
+            .iter()
+            .map(|edge| {
+                let child = &self.tree[edge.child_index];
+
+                ActionStatistics {
+                    action: edge.action,
+                    visit_count: child.visit_count(),
+                    mean_value: child.mean_value(),
+                    ucb: ucb(
+                        child.mean_value(),
+                        child.visit_count() as f32,
+                        root.visit_count() as f32,
+                        self.config.exploration_constant(),
+                    ),
+                    prior: 1.0 / num_legal_moves as f32,
+                    proof: child.proof(),
+                }
+            })
+            .collect();
+        actions.sort_by_key(|statistics| Reverse(statistics.visit_count));
+        actions
+    }
+
+    /// Renders the tree as a Graphviz DOT graph, labelling every node with its visit count and
+    /// mean value, and every edge with its action
+    #[allow(dead_code)] // Used inside tests
+    pub fn to_dot(&self, options: ExportOptions) -> String {
+        let mut dot = String::from("digraph mcts {\n    node [shape=box];\n");
+        let exported_nodes = self.exported_nodes(options);
+        let exported_indices: HashSet<usize> = exported_nodes
+            .iter()
+            .map(|&(node_index, _)| node_index)
+            .collect();
+
+        for (node_index, _) in exported_nodes {
+            let node = &self.tree[node_index];
+            let name = if node_index == 0 { "root\\n" } else { "" };
+
+            // Writing to a String can not fail
+            let _ = writeln!(
+                dot,
+                "    {} [label=\"{}visits: {}\\nvalue: {:.3}\"];",
+                node_index,
+                name,
+                node.visit_count(),
+                node.mean_value()
+            );
+            for edge in node.children().iter() {
+                if exported_indices.contains(&edge.child_index) {
+                    let action = format!("{:?}", edge.action).replace('"', "\\\"");
+                    let _ = writeln!(
+                        dot,
+                        "    {} -> {} [label=\"{}\"];",
+                        node_index, edge.child_index, action
+                    );
                 }
             }
-            node = &self.tree[best_child_index];
         }
-        node.index
+
+        dot.push_str("}\n");
+        dot
     }
 
-    /// Adds a new child to the node at the given index by selecting a random action
-    /// Also updates the given node's state and legal moves left based on the random chosen action
-    fn expand(&mut self, node_index: usize) -> usize {
-        let node = &self.tree[node_index];
-        let (action_index, action) = self.get_random_action(&node.legal_moves);
-        let next_state = self.game.apply_move(&node.state, -node.player, action);
-        let new_legal_moves = self.game.get_legal_moves(&next_state);
-
-        let child = Node::new(
-            next_state,
-            -node.player,
-            new_legal_moves,
-            Some(action),
-            self.tree.len(),
-            Some(node_index),
-        );
+    /// Returns the exported nodes with their depth, parents always come before their children.
+    /// A node reached through several parents is exported once, at its smallest depth
+    fn exported_nodes(&self, options: ExportOptions) -> Vec<(usize, usize)> {
+        let mut exported_nodes = vec![];
+        let mut queued = HashSet::from([0]);
+        let mut queue = VecDeque::from([(0, 0)]);
 
-        // Remove so that the random action does not create the same child twice
-        self.tree[node_index].legal_moves.remove(action_index);
+        while let Some((node_index, depth)) = queue.pop_front() {
+            exported_nodes.push((node_index, depth));
+            if options
+                .max_depth
+                .is_some_and(|max_depth| depth >= max_depth)
+            {
+                continue;
+            }
 
-        // The left over legal moves are those which have not been used up as new a child node
-        self.tree[node_index].children_indices.push(child.index);
-        self.tree.push(child);
+            for edge in self.tree[node_index].children().iter() {
+                if self.tree[edge.child_index].visit_count() >= options.min_visits
+                    && queued.insert(edge.child_index)
+                {
+                    queue.push_back((edge.child_index, depth + 1));
+                }
+            }
+        }
+
+        exported_nodes
+    }
+
+    /// The depth of every node in the tree, indexed the same way as the tree
+    fn node_depths(&self) -> Vec<usize> {
+        // Nodes are only ever added after their parent, so one pass in index order is enough
+        let mut depths = vec![0; self.tree.len()];
+        for node in &self.tree[1..] {
+            #[allow(clippy::unwrap_used)]
+            let parent_index = node.parent_index.unwrap();
+            depths[node.index] = depths[parent_index] + 1;
+        }
+        depths
+    }
+
+    pub fn root_position(&self) -> &Position<G> {
+        &self.tree[0].position
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn root_visit_count(&self) -> u32 {
+        self.tree[0].visit_count()
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn node_count(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// The RAVE visits and RAVE value sum of every expanded action of the root
+    #[allow(dead_code)] // Used inside tests
+    pub fn root_rave_statistics(&self) -> Vec<(G::Action, u32, f32)> {
+        self.tree[0]
+            .children()
+            .iter()
+            .map(|edge| {
+                (
+                    edge.action,
+                    edge.rave_visit_count.load(Ordering::Relaxed),
+                    edge.rave_value_sum.load(),
+                )
+            })
+            .collect()
+    }
+
+    /// Takes the subtree of the given node out of the tree, as a new tree with that node as its
+    /// root. Nodes are added breadth first, so all indices are reassigned
+    fn take_subtree(&mut self, node_index: usize) -> Vec<Node<G>> {
+        let mut old_tree: Vec<Option<Node<G>>> = std::mem::take(&mut self.tree)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut new_tree: Vec<Node<G>> = vec![];
+        // With transpositions a node can be reached through several parents, it is only moved once
+        let mut new_indices: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([(node_index, None)]);
 
+        while let Some((old_index, new_parent)) = queue.pop_front() {
+            let new_index = match new_indices.get(&old_index) {
+                Some(&new_index) => new_index,
+                None => {
+                    #[allow(clippy::unwrap_used)]
+                    let mut node = old_tree[old_index].take().unwrap();
+                    let new_index = new_tree.len();
+
+                    for edge in node.children_mut().drain(..) {
+                        queue.push_back((edge.child_index, Some((new_index, edge))));
+                    }
+
+                    node.index = new_index;
+                    node.parent_index = new_parent
+                        .as_ref()
+                        .map(|(new_parent_index, _)| *new_parent_index);
+                    new_tree.push(node);
+                    new_indices.insert(old_index, new_index);
+                    new_index
+                }
+            };
+
+            if let Some((new_parent_index, mut edge)) = new_parent {
+                edge.child_index = new_index;
+                new_tree[new_parent_index].children_mut().push(edge);
+            }
+        }
+
+        new_tree
+    }
+
+    /// Chooses the move to play with the final move strategy of the config
+    fn get_best_action(&mut self) -> G::Action {
+        let actions = self.action_statistics();
         #[allow(clippy::unwrap_used)]
-        self.tree.last().unwrap().index
+        final_move::choose_action(self.config.final_move(), &actions, &mut self.rng).unwrap()
     }
 
-    /// Simulates a game into future based of the given nodes' state
-    /// Returns the result/value of that game at the end, while accounting for the change of perspective.
-    fn simulate(&'a self, node_index: usize) -> f32 {
-        let node = &self.tree[node_index];
-        let (value, terminated) = self.game.get_value_and_terminated(&node.state, node.player);
+    /// Returns the action and index of the given node's most visited child, the first one on ties
+    fn most_visited_child(&self, node: &Node<G>) -> Option<(G::Action, usize)> {
+        node.children()
+            .iter()
+            .reduce(|best_edge, edge| {
+                if self.tree[edge.child_index].visit_count()
+                    > self.tree[best_edge.child_index].visit_count()
+                {
+                    edge
+                } else {
+                    best_edge
+                }
+            })
+            .map(|edge| (edge.action, edge.child_index))
+    }
+}
+
+/// The limits a run of the workers stops at, a stop request always stops them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Limits {
+    /// Any of the limits of the config
+    Config,
+    /// Only the given number of searches
+    Searches(u32),
+    /// Only a stop request
+    StopRequest,
+}
+
+/// Searches a tree that can be shared with other workers, each running on its own thread
+struct Worker<'a, G: Game> {
+    config: &'a MctsConfig,
+    game: &'a G,
+    tree: &'a Arena<Node<G>>,
+    transpositions: Option<&'a Mutex<HashMap<u64, usize>>>,
+    rollout_policy: &'a dyn RolloutPolicy<G>,
+    selection_policy: &'a dyn SelectionPolicy,
+    stop_requested: &'a AtomicBool,
+    limits: Limits,
+    start: Instant,
+    /// The number of searches started by all workers together
+    num_searches: AtomicU32,
+}
+
+impl<G: Game> Worker<'_, G> {
+    /// Searches until any of the limits is reached, a stop is requested or the root is proven,
+    /// always searching at least once
+    fn run(&self, rng: &mut StdRng) {
+        loop {
+            // A search is claimed before it starts, so the workers together never search more
+            // often than the config allows
+            let search_index = self.num_searches.fetch_add(1, Ordering::Relaxed);
+            let max_searches = match self.limits {
+                Limits::Config => self.config.num_searches(),
+                Limits::Searches(num_searches) => Some(num_searches),
+                Limits::StopRequest => None,
+            };
+            if max_searches.is_some_and(|max_searches| search_index >= max_searches) {
+                break;
+            }
+
+            self.search_once(rng);
+
+            let limit_reached = self.limits == Limits::Config
+                && (self
+                    .config
+                    .max_duration()
+                    .is_some_and(|max_duration| self.start.elapsed() >= max_duration)
+                    || self
+                        .config
+                        .max_nodes()
+                        .is_some_and(|max_nodes| self.tree.len() >= max_nodes));
+
+            // Nothing is left to search once the solver has proven the root
+            let root_proven = self.tree[0].proof() != Proof::Unproven;
+
+            if limit_reached || root_proven || self.stop_requested.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+    }
+
+    /// One iteration of the search: selection, expansion, simulation and backpropagation
+    fn search_once(&self, rng: &mut StdRng) {
+        let mut path = self.select(rng);
+        let node_index = path[path.len() - 1];
+
+        let (value, terminated) = {
+            let node = &self.tree[node_index];
+            match node.proof().value() {
+                // The value of a proven node is known, so it needs no more searching
+                Some(value) => (value, true),
+                None => (
+                    node.position
+                        .outcome()
+                        .reward(node.position.last_mover(self.game)),
+                    node.position.is_over(),
+                ),
+            }
+        };
+
+        // The moves of the rollout, only recorded for the RAVE statistics
+        let mut rollout_moves = vec![];
 
-        // Inverd value because the child is the perspective of the opponent's relative to the parnet
-        // If child won, then that would not be good for the parent, so the value is inverted
         if terminated {
-            return -value;
+            self.backpropagate(&path, &rollout_moves, value);
+            return;
         }
 
-        let mut rollout_state = node.state.clone();
-        let mut rollout_player = -node.player;
+        let (child_index, position) = self.expand(node_index, rng);
+        if child_index != node_index {
+            path.push(child_index);
+        }
+        let value = self.simulate(position, rng, &mut rollout_moves);
+        self.backpropagate(&path, &rollout_moves, value);
+    }
 
-        loop {
-            let index_action = self.get_random_action(&self.game.get_legal_moves(&rollout_state));
+    /// Walks down from the root, taking the child with the best UCB value, until it reaches a node
+    /// that is not fully expanded or is proven. Returns the path taken, every node on it gets a
+    /// virtual loss until backpropagation. Children proven to lose for the player to move are
+    /// skipped
+    #[allow(non_snake_case)]
+    fn select(&self, rng: &mut StdRng) -> Vec<usize> {
+        let tree = self.tree;
+        let mut node = &tree[0];
+        node.virtual_loss.fetch_add(1, Ordering::Relaxed);
+        let mut path = vec![node.index];
 
-            rollout_state = self
-                .game
-                .apply_move(&rollout_state, rollout_player, index_action.1);
+        while node.is_fully_expanded() && node.proof() == Proof::Unproven {
+            let node_children = node.children();
+            let mut edges: Vec<&Edge<G>> = node_children
+                .iter()
+                .filter(|edge| tree[edge.child_index].proof() != Proof::Loss)
+                .collect();
+            // Only when another worker proved the last child in the meantime
+            if edges.is_empty() {
+                edges = node_children.iter().collect();
+            }
 
-            let (mut value, terminated) = self
-                .game
-                .get_value_and_terminated(&rollout_state, rollout_player);
+            let children: Vec<ChildStatistics> = edges
+                .iter()
+                .map(|edge| {
+                    self.child_statistics(node_children.len(), edge, &tree[edge.child_index])
+                })
+                .collect();
+            let chosen_child =
+                self.selection_policy
+                    .select(node.visit_count() as f32, &children, rng);
 
-            if terminated {
-                // Sets the value back to parents perspective
-                if self.tree[node_index].player == rollout_player && value != 0.5 {
-                    value = -value;
-                }
-                println!("{}", value);
+            node = &tree[edges[chosen_child].child_index];
+            drop(node_children);
+            node.virtual_loss.fetch_add(1, Ordering::Relaxed);
+            path.push(node.index);
+        }
+        path
+    }
+
+    /// Adds a new child to the node at the given index by selecting a random action
+    /// Also updates the given node's legal moves left based on the random chosen action
+    /// Returns the index and position of the node to simulate from. Only the node itself is
+    /// locked, other workers keep searching the rest of the tree in the meantime
+    fn expand(&self, node_index: usize, rng: &mut StdRng) -> (usize, Position<G>) {
+        let node = &self.tree[node_index];
+        // Held until the child is added, so no worker sees the action in neither the legal moves
+        // nor the children. The legal moves of a node are always locked before its children
+        let mut legal_moves = node.legal_moves();
+
+        // Another worker expanded the last legal move since this node was selected
+        if legal_moves.is_empty() {
+            return (node_index, node.position.clone());
+        }
+
+        let (action_index, action) = Self::get_random_action(rng, &legal_moves);
+        // Remove so that the random action does not create the same child twice
+        legal_moves.remove(action_index);
+        let next_position = node.position.child(self.game, action);
+
+        // Held until the new node is in the table, so two workers can not both add the position
+        let mut transpositions = self.transpositions.map(|transpositions| {
+            transpositions
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+        });
+        let hash = position_hash(self.game, &next_position);
+
+        // The position was already reached through another move order, so the node is shared
+        let transposition = transpositions
+            .as_ref()
+            .and_then(|transpositions| transpositions.get(&hash).copied())
+            .filter(|&index| self.tree[index].position == next_position);
+        if let Some(child_index) = transposition {
+            self.tree[child_index]
+                .virtual_loss
+                .fetch_add(1, Ordering::Relaxed);
+            Self::add_child(node, Edge::new(action, child_index));
+            return (child_index, next_position);
+        }
+
+        let child_legal_moves = self.game.legal_actions(next_position.state());
+        let child_index = self.tree.push_with(|child_index| {
+            let child = Node::new(
+                next_position.clone(),
+                child_legal_moves,
+                child_index,
+                Some(node_index),
+            );
+            // The child is part of this search's path, like the nodes selected before it
+            child.virtual_loss.store(1, Ordering::Relaxed);
+            child
+        });
+
+        // The left over legal moves are those which have not been used up as new a child node
+        Self::add_child(node, Edge::new(action, child_index));
+        if let Some(transpositions) = transpositions.as_mut() {
+            transpositions.entry(hash).or_insert(child_index);
+        }
+
+        (child_index, next_position)
+    }
 
-                return value;
+    /// Adds the edge to the children of the node, which the other workers see from then on
+    fn add_child(node: &Node<G>, edge: Edge<G>) {
+        node.children
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(edge);
+    }
+
+    /// Simulates a game into future based of the given position, returning the reward of the
+    /// outcome of that game for the player that made the last move, the same perspective the
+    /// node's value is stored in.
+    /// The rollout policy of the config chooses the moves, the moves played are added to
+    /// `rollout_moves` when RAVE is used
+    fn simulate(
+        &self,
+        mut position: Position<G>,
+        rng: &mut StdRng,
+        rollout_moves: &mut Vec<(G::Player, G::Action)>,
+    ) -> f32 {
+        let player = position.last_mover(self.game);
+        let mut depth = 0;
+
+        let value = loop {
+            if position.is_over() {
+                // From the node's perspective, a win for the opponent is a loss
+                break position.outcome().reward(player);
             }
 
-            rollout_player *= -1;
+            if self.rollout_policy.max_depth() == Some(depth) {
+                let last_mover = position.last_mover(self.game);
+                let value = self.game.evaluate(position.state(), last_mover);
+                break if last_mover == player { value } else { -value };
+            }
+
+            let legal_moves = self.game.legal_actions(position.state());
+            let action = self.rollout_policy.choose_action(
+                self.game,
+                position.state(),
+                position.to_move(),
+                &legal_moves,
+                rng,
+            );
+            position.play(self.game, action);
+            depth += 1;
+        };
+
+        if self.config.rave().is_some() {
+            rollout_moves.extend(position.history());
         }
+        value
     }
 
-    /// Backpropagates the given value to all of the given node's parents
-    /// While accounting for the difference in perspectives while going up the tree
-    fn backpropagate(&mut self, node_index: usize, value: f32) {
-        self.tree[node_index].value_sum += value;
-        self.tree[node_index].visit_count += 1;
+    /// Backpropagates the given value from the last node of the path up to the root, removing the
+    /// virtual losses of this search on the way. The path is followed rather than the parents, as
+    /// with transpositions a node can have several
+    /// While accounting for the difference in perspectives while going up the tree, a reward for one
+    /// player is the negated reward for the other
+    fn backpropagate(
+        &self,
+        path: &[usize],
+        rollout_moves: &[(G::Player, G::Action)],
+        mut value: f32,
+    ) {
+        let tree = self.tree;
+        self.prove(path);
+
+        // Every move played in this search, from the root to the end of the rollout
+        let mut played_moves: Vec<(G::Player, G::Action)> = vec![];
+        if self.config.rave().is_some() {
+            for parent_and_child in path.windows(2) {
+                let child = &tree[parent_and_child[1]];
+                let parent = &tree[parent_and_child[0]];
+                let action = parent
+                    .children()
+                    .iter()
+                    .find(|edge| edge.child_index == child.index)
+                    .map(|edge| edge.action);
+                if let Some(action) = action {
+                    played_moves.push((parent.position.to_move(), action));
+                }
+            }
+            played_moves.extend_from_slice(rollout_moves);
+        }
 
-        let parent_index = self.tree[node_index].parent_index;
+        for (depth, &node_index) in path.iter().enumerate().rev() {
+            let node = &tree[node_index];
+            node.value_sum.fetch_add(value);
+            node.value_squared_sum.fetch_add(value * value);
+            node.visit_count.fetch_add(1, Ordering::Relaxed);
+            node.virtual_loss.fetch_sub(1, Ordering::Relaxed);
 
-        if let Some(valid_parent_index) = parent_index {
-            self.backpropagate(valid_parent_index, -value);
+            if self.config.rave().is_some() {
+                // The moves played after this node by the player to move in it, valued from that
+                // player's perspective
+                let player_to_move = node.position.to_move();
+                for edge in node.children().iter() {
+                    let was_played = played_moves[depth..]
+                        .iter()
+                        .any(|&(player, action)| player == player_to_move && action == edge.action);
+                    if was_played {
+                        edge.rave_value_sum.fetch_add(-value);
+                        edge.rave_visit_count.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+
+            value = -value;
         }
     }
 
-    /// Gets the child of the root with the most amount of visits and returns the action taken
-    fn get_best_action(&self) -> (usize, usize) {
-        let root = &self.tree[0];
-        let mut best_child_index = 0;
-        let mut highest_visit_count = 0;
+    /// Proves the last node of the path when its game is over, which is exact even without the
+    /// solver and lets an immediate win be told apart from a slower one. The solver then proves the
+    /// nodes above it that are decided by the proofs of their children, stopping at the first that
+    /// is not
+    fn prove(&self, path: &[usize]) {
+        let tree = self.tree;
+        let Some((&leaf_index, ancestors)) = path.split_last() else {
+            return;
+        };
+        let leaf = &tree[leaf_index];
+        if leaf.proof() == Proof::Unproven {
+            if !leaf.position.is_over() {
+                return;
+            }
+            let reward = leaf
+                .position
+                .outcome()
+                .reward(leaf.position.last_mover(self.game));
+            leaf.set_proof(Proof::from_reward(reward));
+        }
+        if !self.config.solver() {
+            return;
+        }
 
-        for child_index in &root.children_indices {
-            let child_index = *child_index;
-            let child = &self.tree[child_index];
-            if child.visit_count > highest_visit_count {
-                best_child_index = child_index;
-                highest_visit_count = child.visit_count;
+        for &node_index in ancestors.iter().rev() {
+            let node = &tree[node_index];
+            let proof = Self::proof_from_children(tree, node);
+            if proof == Proof::Unproven {
+                break;
             }
+            node.set_proof(proof);
         }
+    }
 
-        #[allow(clippy::unwrap_used)]
-        self.tree[best_child_index].action_taken.unwrap()
+    /// The proof of the node decided by its children, which are moves of the player to move: a
+    /// loss when that player can win, otherwise a win or draw once every move is proven
+    fn proof_from_children(tree: &Arena<Node<G>>, node: &Node<G>) -> Proof {
+        let legal_moves = node.legal_moves();
+        let child_proofs: Vec<Proof> = node
+            .children()
+            .iter()
+            .map(|edge| tree[edge.child_index].proof())
+            .collect();
+
+        if child_proofs.contains(&Proof::Win) {
+            Proof::Loss
+        } else if !legal_moves.is_empty() || child_proofs.contains(&Proof::Unproven) {
+            Proof::Unproven
+        } else if child_proofs.contains(&Proof::Draw) {
+            Proof::Draw
+        } else {
+            Proof::Win
+        }
     }
 
     /// Choses a random action based on the given node's legal moves left
-    fn get_random_action(&self, legal_moves: &[(usize, usize)]) -> (usize, (usize, usize)) {
-        let chosen_index = rand::random_range(0..legal_moves.len());
+    fn get_random_action(rng: &mut StdRng, legal_moves: &[G::Action]) -> (usize, G::Action) {
+        let chosen_index = rng.random_range(0..legal_moves.len());
         (chosen_index, legal_moves[chosen_index])
     }
 
-    /// Calculates the UCB for the child, used to determine what 'path' the selection phase should
-    /// take in order to get the desired node.
-    #[allow(non_snake_case)]
-    fn calculate_UCB(&self, parent: &Node, child: &Node) -> f32 {
-        let w: f32 = child.value_sum;
-        let n: f32 = child.visit_count as f32;
-        let N: f32 = parent.visit_count as f32;
+    /// Gathers the statistics the selection policy chooses the child by, used to determine what
+    /// 'path' the selection phase should take. The virtual losses of the child count as visits
+    /// that were lost. With RAVE the value of the child is blended with its RAVE value, trusting
+    /// the RAVE value less as the child gets more visits
+    fn child_statistics(
+        &self,
+        num_children: usize,
+        edge: &Edge<G>,
+        child: &Node<G>,
+    ) -> ChildStatistics {
+        let virtual_loss = child.virtual_loss.load(Ordering::Relaxed) as f32;
+        let n: f32 = child.visit_count() as f32 + virtual_loss;
+
+        // Every virtual loss is a value of -1.0, which squared adds 1.0
+        let (mut q, squared_q): (f32, f32) = if n > 0.0 {
+            (
+                (child.value_sum.load() - virtual_loss) / n,
+                (child.value_squared_sum.load() + virtual_loss) / n,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        let rave_n = edge.rave_visit_count.load(Ordering::Relaxed) as f32;
+        if let (Some(schedule), true) = (self.config.rave(), rave_n > 0.0) {
+            let rave_q = edge.rave_value_sum.load() / rave_n;
+            let beta = schedule.beta(n, rave_n);
+            q = (1.0 - beta) * q + beta * rave_q;
+        }
 
-        // The constant representing the confidence of the model, the higher this value, the less the win_odds matters in the formula
-        // Lower means Exploitation, higher means Exploration
-        let C: f32 = self.args["C"];
+        ChildStatistics {
+            visit_count: n,
+            mean_value: q,
+            mean_squared_value: squared_q,
+            // The parent is fully expanded, so its children are all of its legal moves
+            prior: 1.0 / num_children as f32,
+        }
+    }
+}
 
-        let win_odds_child: f32 = if n > 0.0 { w / n } else { 0.0 };
+impl<G: Game> Mcts<G>
+where
+    G::State: Serialize,
+    G::Action: Serialize,
+    G::Player: Serialize,
+{
+    /// Serializes the tree as JSON, a list of nodes with their board, visit count, mean value and
+    /// the actions leading to their exported children
+    #[allow(dead_code)] // Used inside tests
+    pub fn to_json(&self, options: ExportOptions) -> Result<String> {
+        let exported_nodes = self.exported_nodes(options);
+        let exported_indices: HashSet<usize> = exported_nodes
+            .iter()
+            .map(|&(node_index, _)| node_index)
+            .collect();
 
-        let q: f32 = win_odds_child;
+        let nodes: Vec<_> = exported_nodes
+            .iter()
+            .map(|&(node_index, depth)| {
+                let node = &self.tree[node_index];
+                let children: Vec<_> = node
+                    .children()
+                    .iter()
+                    .filter(|edge| exported_indices.contains(&edge.child_index))
+                    .map(|edge| json!({ "action": edge.action, "index": edge.child_index }))
+                    .collect();
 
-        let UCB: f32 = q + C * f32::sqrt(N.ln_1p() / n);
+                json!({
+                    "index": node_index,
+                    "children": children,
+                    "depth": depth,
+                    "to_move": node.position.to_move(),
+                    "board": node.position.state(),
+                    "visit_count": node.visit_count(),
+                    "mean_value": node.mean_value(),
+                    "proof": node.proof(),
+                })
+            })
+            .collect();
 
-        UCB
+        serde_json::to_string_pretty(&json!({ "nodes": nodes }))
+            .context("Unable to serialize the search tree")
     }
 }
@@ -0,0 +1,184 @@
+use std::cmp::Reverse;
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::{
+    config::{FinalMove, MctsConfig, Parallelism},
+    final_move,
+    games::{Game, Position},
+    mcts::{ActionStatistics, Mcts, Proof, SearchResult},
+    selection::ucb,
+};
+
+/// Searches with multiple threads. With root parallelization independent trees search the same
+/// position, each on its own thread with its own seed, and the statistics of their root children
+/// are merged to choose a move. With tree parallelization all threads search a single shared tree
+pub struct ParallelMcts<G: Game> {
+    config: MctsConfig,
+    trees: Vec<Mcts<G>>,
+    /// Used to choose the final move, when the strategy of the config is random
+    rng: StdRng,
+}
+
+impl<G: Game + Clone> ParallelMcts<G> {
+    /// Creates `num_threads` trees, the first tree uses the seed of the config so a single thread
+    /// searches the same way as `Mcts`, the others use the following seeds. With tree
+    /// parallelization a single tree is created, which is searched by all threads
+    pub fn new(config: MctsConfig, game: G, root: &Position<G>) -> ParallelMcts<G> {
+        let seed = config.seed().unwrap_or_else(rand::random);
+        let num_trees = match config.parallelism() {
+            Parallelism::Root => config.num_threads(),
+            Parallelism::Tree => 1,
+        };
+        let trees = (0..num_trees as u64)
+            .map(|tree_index| {
+                let tree_config = config.clone().with_seed(seed.wrapping_add(tree_index));
+                Mcts::new(tree_config, game.clone(), root)
+            })
+            .collect();
+
+        ParallelMcts {
+            config,
+            trees,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Searches every tree on its own thread until its limits are reached, then chooses the
+    /// action with the final move strategy of the config, out of the statistics of all trees.
+    /// A max-robust search keeps searching every tree until the merged statistics are robust
+    pub fn search(&mut self) -> G::Action {
+        // The trees only search until their limits, the merged statistics decide on robustness
+        thread::scope(|scope| {
+            for tree in &mut self.trees {
+                scope.spawn(move || tree.search_until_limits());
+            }
+        });
+
+        if let FinalMove::MaxRobust { max_extra_searches } = self.config.final_move() {
+            final_move::search_until_robust(
+                self,
+                max_extra_searches,
+                |search| search.search_result().actions,
+                Self::search_more,
+            );
+        }
+
+        let actions = self.search_result().actions;
+        #[allow(clippy::unwrap_used)]
+        final_move::choose_action(self.config.final_move(), &actions, &mut self.rng).unwrap()
+    }
+
+    /// Adds the given number of searches to every tree, each on its own thread
+    fn search_more(&mut self, num_searches: u32) {
+        thread::scope(|scope| {
+            for tree in &mut self.trees {
+                scope.spawn(move || tree.search_more(num_searches));
+            }
+        });
+    }
+
+    /// Moves the root of every tree to the child reached by the given action
+    pub fn advance(&mut self, action: G::Action) {
+        for tree in &mut self.trees {
+            tree.advance(action);
+        }
+    }
+
+    pub fn root_position(&self) -> &Position<G> {
+        self.trees[0].root_position()
+    }
+
+    /// Merges the search results of all trees, summing the visits of every root action. The
+    /// principal variation is taken from the tree that visited the best action the most
+    pub fn search_result(&self) -> SearchResult<G::Action> {
+        let results: Vec<SearchResult<G::Action>> =
+            self.trees.iter().map(|tree| tree.search_result()).collect();
+
+        // (action, visit count, value sum, prior, proof), proofs are the same in every tree that
+        // proved them
+        let mut merged: Vec<(G::Action, u32, f32, f32, Proof)> = vec![];
+        for statistics in results.iter().flat_map(|result| &result.actions) {
+            let value_sum = statistics.mean_value * statistics.visit_count as f32;
+            match merged
+                .iter_mut()
+                .find(|(action, ..)| *action == statistics.action)
+            {
+                Some((_, visit_count, merged_value_sum, _, proof)) => {
+                    *visit_count += statistics.visit_count;
+                    *merged_value_sum += value_sum;
+                    if *proof == Proof::Unproven {
+                        *proof = statistics.proof;
+                    }
+                }
+                None => merged.push((
+                    statistics.action,
+                    statistics.visit_count,
+                    value_sum,
+                    statistics.prior,
+                    statistics.proof,
+                )),
+            }
+        }
+
+        let root_visit_count: u32 = merged.iter().map(|&(_, visit_count, ..)| visit_count).sum();
+        let mut actions: Vec<ActionStatistics<G::Action>> = merged
+            .into_iter()
+            .map(|(action, visit_count, value_sum, prior, proof)| {
+                let mean_value = if visit_count > 0 {
+                    value_sum / visit_count as f32
+                } else {
+                    0.0
+                };
+
+                ActionStatistics {
+                    action,
+                    visit_count,
+                    mean_value,
+                    ucb: ucb(
+                        mean_value,
+                        visit_count as f32,
+                        root_visit_count as f32,
+                        self.config.exploration_constant(),
+                    ),
+                    prior,
+                    proof,
+                }
+            })
+            .collect();
+        // Stable, so ties keep the order of the first tree like `Mcts` does
+        actions.sort_by_key(|statistics| Reverse(statistics.visit_count));
+
+        let principal_variation = actions
+            .first()
+            .and_then(|best| {
+                results.iter().max_by_key(|result| {
+                    result
+                        .actions
+                        .iter()
+                        .find(|statistics| statistics.action == best.action)
+                        .map_or(0, |statistics| statistics.visit_count)
+                })
+            })
+            .map(|result| result.principal_variation.clone())
+            .unwrap_or_default();
+
+        SearchResult {
+            actions,
+            principal_variation,
+            node_count: results.iter().map(|result| result.node_count).sum(),
+            max_depth: results
+                .iter()
+                .map(|result| result.max_depth)
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    #[allow(dead_code)] // Used inside tests
+    pub fn trees(&self) -> &[Mcts<G>] {
+        &self.trees
+    }
+}
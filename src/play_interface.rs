@@ -1,15 +1,157 @@
-use std::{
-    collections::HashMap,
-    io::{stdin, stdout, Write},
+use std::io::{stdin, stdout, Write};
+
+use crate::{
+    config::MctsConfig,
+    games::{
+        BitboardTicTacToe, ConnectFour, Game, Move, Outcome, Player, Position, TicTacToe,
+        UltimateTicTacToe,
+    },
+    parallel::ParallelMcts,
+    solver::Solver,
 };
+use anyhow::{anyhow, Result};
 
-use anyhow::Result;
-use ndarray::Array2;
+/// The game specific parts of playing a game in the terminal
+pub trait Playable: Game<Player = Player> + Clone {
+    /// Explains how a move should be entered
+    fn input_hint(&self) -> String;
 
-use crate::{games::TicTacToe, mcts::Mcts};
+    /// Parses the entered move, or explains why it is not a valid move
+    fn parse_action(&self, input: &str) -> Result<Self::Action>;
 
-pub fn choose_play_option() -> Result<()> {
-    println!("Play against MCTS (option '1') or let MCTS play against itself (option '2')? ");
+    /// Plays the entered move, or explains why it can not be played in the given state
+    fn try_apply(
+        &self,
+        state: &Self::State,
+        player: Player,
+        action: Self::Action,
+    ) -> Result<Self::State>;
+
+    fn print(&self, state: &Self::State) -> Result<()>;
+}
+
+impl Playable for TicTacToe {
+    fn input_hint(&self) -> String {
+        "Enter the row and then the column, such as '01' or '0 1'".to_string()
+    }
+
+    fn parse_action(&self, input: &str) -> Result<Move> {
+        if let Some((row, col)) = parse_position(input) {
+            if row < self.row_count && col < self.column_count {
+                return Ok(Move(row, col));
+            }
+        }
+
+        Err(anyhow!(
+            "please only provide 2 numbers, a row between 0 and {} and a column between 0 and {}",
+            self.row_count - 1,
+            self.column_count - 1
+        ))
+    }
+
+    fn try_apply(
+        &self,
+        state: &Self::State,
+        player: Player,
+        action: Self::Action,
+    ) -> Result<Self::State> {
+        self.try_apply_move(state, player, action)
+    }
+
+    fn print(&self, state: &Self::State) -> Result<()> {
+        self.print_state(state)
+    }
+}
+
+impl Playable for BitboardTicTacToe {
+    fn input_hint(&self) -> String {
+        self.board().input_hint()
+    }
+
+    fn parse_action(&self, input: &str) -> Result<Move> {
+        self.board().parse_action(input)
+    }
+
+    fn try_apply(
+        &self,
+        state: &Self::State,
+        player: Player,
+        action: Self::Action,
+    ) -> Result<Self::State> {
+        self.try_apply_move(state, player, action)
+    }
+
+    fn print(&self, state: &Self::State) -> Result<()> {
+        self.print_state(state)
+    }
+}
+
+impl Playable for UltimateTicTacToe {
+    fn input_hint(&self) -> String {
+        "Enter the row and then the column of the 9x9 board, such as '48' or '4 8'".to_string()
+    }
+
+    fn parse_action(&self, input: &str) -> Result<Move> {
+        match parse_position(input) {
+            Some((row, col)) if row < 9 && col < 9 => Ok(Move(row, col)),
+            _ => Err(anyhow!(
+                "please only provide 2 numbers, a row and a column between 0 and 8"
+            )),
+        }
+    }
+
+    fn try_apply(
+        &self,
+        state: &Self::State,
+        player: Player,
+        action: Self::Action,
+    ) -> Result<Self::State> {
+        self.try_apply_move(state, player, action)
+    }
+
+    fn print(&self, state: &Self::State) -> Result<()> {
+        self.print_state(state)
+    }
+}
+
+impl Playable for ConnectFour {
+    fn input_hint(&self) -> String {
+        "Enter the column, such as '3'".to_string()
+    }
+
+    fn parse_action(&self, input: &str) -> Result<usize> {
+        match input.trim().parse::<usize>() {
+            Ok(column) if column < self.column_count => Ok(column),
+            _ => Err(anyhow!(
+                "please only provide 1 number, a column between 0 and {}",
+                self.column_count - 1
+            )),
+        }
+    }
+
+    fn try_apply(
+        &self,
+        state: &Self::State,
+        player: Player,
+        action: Self::Action,
+    ) -> Result<Self::State> {
+        self.try_apply_move(state, player, action)
+    }
+
+    fn print(&self, state: &Self::State) -> Result<()> {
+        self.print_state(state)
+    }
+}
+
+enum ChosenGame {
+    TicTacToe(TicTacToe),
+    BitboardTicTacToe(BitboardTicTacToe),
+    ConnectFour(ConnectFour),
+    UltimateTicTacToe(UltimateTicTacToe),
+}
+
+pub fn choose_play_option(config: &MctsConfig) -> Result<()> {
+    println!("Play against MCTS (option '1'), let MCTS play against itself (option '2') or play against the perfect 3x3 Tic Tac Toe solver (option '3')? ");
     print!("Choose here (to quit, press 'q'): ");
     let chosen_option = loop {
         let input = get_input()?;
@@ -19,34 +161,117 @@ pub fn choose_play_option() -> Result<()> {
                 break 0;
             }
             if let Some(option) = chosen_option.to_digit(10) {
-                if option > 0 && option < 3 {
+                if option > 0 && option < 4 {
                     break option as usize;
                 }
             }
         }
 
         print!(
-            "Invalid syntax (\"{}\"), please only provide 1 number, a '1' or a '2': ",
+            "Invalid syntax (\"{}\"), please only provide 1 number, a '1', '2' or '3': ",
             input
         );
     };
 
     match chosen_option {
         1 => {
+            let chosen_game = choose_game()?;
             println!("Good luck and have fun!");
-            player_vs_mcts()?;
-            choose_play_option()
+            match chosen_game {
+                ChosenGame::TicTacToe(game) => player_vs_mcts(config, &game)?,
+                ChosenGame::BitboardTicTacToe(game) => player_vs_mcts(config, &game)?,
+                ChosenGame::ConnectFour(game) => player_vs_mcts(config, &game)?,
+                ChosenGame::UltimateTicTacToe(game) => player_vs_mcts(config, &game)?,
+            }
+            choose_play_option(config)
         }
 
         2 => {
+            let chosen_game = choose_game()?;
             println!("Watch them fight!");
-            self_play()?;
-            choose_play_option()
+            match chosen_game {
+                ChosenGame::TicTacToe(game) => self_play(config, &game)?,
+                ChosenGame::BitboardTicTacToe(game) => self_play(config, &game)?,
+                ChosenGame::ConnectFour(game) => self_play(config, &game)?,
+                ChosenGame::UltimateTicTacToe(game) => self_play(config, &game)?,
+            };
+            choose_play_option(config)
+        }
+
+        3 => {
+            // The solver searches every position, which only finishes in time on the 3x3 board
+            println!("Good luck, you will need it!");
+            player_vs_solver(&TicTacToe::init())?;
+            choose_play_option(config)
         }
         _ => Ok(()),
     }
 }
 
+fn choose_game() -> Result<ChosenGame> {
+    print!("Which game, Tic Tac Toe (option '1'), Connect Four (option '2') or Ultimate Tic Tac Toe (option '3')? ");
+    let chosen_game = loop {
+        let input = get_input()?;
+
+        if let [chosen_game] = input.chars().collect::<Vec<_>>()[..] {
+            if let Some(option) = chosen_game.to_digit(10) {
+                if option > 0 && option < 4 {
+                    break option;
+                }
+            }
+        }
+
+        print!(
+            "Invalid syntax (\"{}\"), please only provide 1 number, a '1', '2' or '3': ",
+            input
+        );
+    };
+
+    match chosen_game {
+        1 => {
+            // Boards that fit in a bitboard play the same, but search faster
+            let board = choose_board()?;
+            Ok(
+                match BitboardTicTacToe::new(board.row_count, board.column_count, board.win_length)
+                {
+                    Ok(game) => ChosenGame::BitboardTicTacToe(game),
+                    Err(_) => ChosenGame::TicTacToe(board),
+                },
+            )
+        }
+        2 => Ok(ChosenGame::ConnectFour(ConnectFour::init())),
+        _ => Ok(ChosenGame::UltimateTicTacToe(UltimateTicTacToe::init())),
+    }
+}
+
+fn choose_board() -> Result<TicTacToe> {
+    print!("Board size as 'rows columns win_length', such as '4 4 3' (press enter for a normal 3x3 game): ");
+    loop {
+        let input = get_input()?;
+        if input.trim().is_empty() {
+            break Ok(TicTacToe::init());
+        }
+
+        let numbers = input
+            .split_whitespace()
+            .map(|number| number.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>();
+
+        if let Ok(&[row_count, column_count, win_length]) = numbers.as_deref() {
+            match TicTacToe::new(row_count, column_count, win_length) {
+                Ok(game) => break Ok(game),
+                Err(error) => print!("{}, please choose another board: ", error),
+            }
+            continue;
+        }
+
+        print!(
+            "Invalid syntax (\"{}\"), please provide 3 numbers, such as '4 4 3': ",
+            input
+        );
+    }
+}
+
 fn get_input() -> Result<String> {
     let mut input = String::new();
 
@@ -64,157 +289,276 @@ fn get_input() -> Result<String> {
     Ok(input)
 }
 
-pub fn self_play() -> Result<f32> {
-    let game = TicTacToe::init();
-    let args: HashMap<&str, f32> =
-        HashMap::from([(("C"), f32::sqrt(2.0)), (("num_searches"), 1000.0)]);
+pub fn self_play<G: Playable>(config: &MctsConfig, game: &G) -> Result<Outcome<Player>> {
+    let mut position = Position::new(game, game.initial_state(), Player::X);
 
-    let mut state = game.get_initial_state();
-    let mut player = 1;
-
-    game.print_state(&state)?;
+    game.print(position.state())?;
+    // One tree is shared by both sides, so every search builds on the previous ones
+    let mut tree = ParallelMcts::new(config.clone(), game.clone(), &position);
     loop {
-        let mut tree = Mcts::new(args.clone(), TicTacToe::init(), &state, player);
         let best_action = tree.search();
-        state = game.apply_move(&state, player, best_action);
-        game.print_state(&state)?;
-        let (value, terminated) = game.get_value_and_terminated(&state, player);
+        tree.advance(best_action);
+        position.play(game, best_action);
+        game.print(position.state())?;
 
-        if terminated {
-            break Ok(value);
+        if position.is_over() {
+            break Ok(position.outcome());
         }
-
-        player = -player;
     }
 }
 
-pub fn player_vs_mcts() -> Result<()> {
-    print!("Which player, X/x or O/o? ");
-    let mut chosen_player: i8;
-    loop {
-        let player_input = get_input()?;
+/// The results of a match, from the perspective of the first config
+#[allow(dead_code)] // Used inside tests
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchResult {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
 
-        chosen_player = match player_input.to_lowercase().trim() {
-            "x" => 1,
-            "o" => -1,
-            _ => 0,
+/// Plays `num_games` games between both configs without printing, to compare them with the same
+/// search limits. The configs take turns starting, and every game uses the next seed of both
+/// configs so the games differ
+#[allow(dead_code)] // Used inside tests
+pub fn head_to_head<G: Playable>(
+    game: &G,
+    first: &MctsConfig,
+    second: &MctsConfig,
+    num_games: u32,
+) -> MatchResult {
+    let mut result = MatchResult::default();
+
+    for game_index in 0..num_games {
+        let seeded = |config: &MctsConfig| {
+            let seed = config.seed().unwrap_or_else(rand::random);
+            config
+                .clone()
+                .with_seed(seed.wrapping_add(game_index as u64))
         };
-
-        if chosen_player == 0 {
-            print!(
-                "Invalid player: \"{}\", please choose one of these: 'X'/'x' or 'O'/'o': ",
-                player_input
-            );
+        let (first, second) = (seeded(first), seeded(second));
+        let first_player = if game_index % 2 == 0 {
+            Player::X
         } else {
-            let chosen_player_as_char = if chosen_player == 1 { "X" } else { "O" };
+            Player::O
+        };
 
-            println!("Player \"{}\" chosen!", chosen_player_as_char);
-            break;
+        let mut position = Position::new(game, game.initial_state(), Player::X);
+        loop {
+            let config = if position.to_move() == first_player {
+                &first
+            } else {
+                &second
+            };
+            let mut tree = ParallelMcts::new(config.clone(), game.clone(), &position);
+            position.play(game, tree.search());
+
+            match position.outcome() {
+                Outcome::Ongoing => {}
+                Outcome::Draw => {
+                    result.draws += 1;
+                    break;
+                }
+                Outcome::Win(winner) => {
+                    if winner == first_player {
+                        result.wins += 1;
+                    } else {
+                        result.losses += 1;
+                    }
+                    break;
+                }
+            }
         }
     }
 
-    let mcts_player = -chosen_player;
-    let game = TicTacToe::init();
-    let mut state = game.get_initial_state();
+    result
+}
+
+pub fn player_vs_mcts<G: Playable>(config: &MctsConfig, game: &G) -> Result<()> {
+    let mut tree = None;
+    play_against(game, "MCTS", |position| {
+        mcts_turn(config, game, &mut tree, position)
+    })
+}
+
+pub fn player_vs_solver(game: &TicTacToe) -> Result<()> {
+    let mut solver = Solver::new(game.clone());
+    play_against(game, "the solver", |position| {
+        solver_turn(&mut solver, game, position)
+    })
+}
+
+/// Lets the player play a game against the opponent, which plays its moves on the position in
+/// `opponent_turn`
+fn play_against<G: Playable>(
+    game: &G,
+    opponent_name: &str,
+    mut opponent_turn: impl FnMut(&mut Position<G>) -> Result<()>,
+) -> Result<()> {
+    print!("Which player, X/x or O/o? ");
+    let chosen_player = loop {
+        let player_input = get_input()?;
 
-    if mcts_player == 1 {
-        state = mcts_turn(&game, &state, mcts_player)?;
+        match player_input.parse::<Player>() {
+            Ok(chosen_player) => {
+                println!("Player \"{}\" chosen!", chosen_player);
+                break chosen_player;
+            }
+            Err(error) => print!("Invalid player: \"{}\", {}: ", player_input, error),
+        }
+    };
+
+    let mut position = Position::new(game, game.initial_state(), Player::X);
+
+    if chosen_player == Player::O {
+        opponent_turn(&mut position)?;
     }
 
-    game.print_state(&state)?;
+    game.print(position.state())?;
     loop {
-        state = player_turn(&game, &state, chosen_player)?;
-        let (value, terminated) = game.get_value_and_terminated(&state, chosen_player);
-
-        if terminated {
-            if value == 1.0 {
-                println!("Congratulations, you beat the MCTS algorithm!")
-            } else {
+        player_turn(game, &mut position)?;
+        match position.outcome() {
+            Outcome::Ongoing => {}
+            Outcome::Win(_) => {
+                println!("Congratulations, you beat {}!", opponent_name);
+                break;
+            }
+            Outcome::Draw => {
                 println!(r"Welp, its a draw ¯\_(ツ)_/¯");
+                break;
             }
-            rematch_option()?;
-            break;
         }
 
-        state = mcts_turn(&game, &state, mcts_player)?;
-        let (value, terminated) = game.get_value_and_terminated(&state, mcts_player);
-
-        if terminated {
-            if value == 1.0 {
-                println!("You lost against MCTS...");
-            } else {
+        opponent_turn(&mut position)?;
+        match position.outcome() {
+            Outcome::Ongoing => {}
+            Outcome::Win(_) => {
+                println!("You lost against {}...", opponent_name);
+                break;
+            }
+            Outcome::Draw => {
                 println!(r"Welp, its a draw ¯\_(ツ)_/¯");
+                break;
             }
-            rematch_option()?;
-            break;
         }
     }
 
+    if rematch_option()? {
+        return play_against(game, opponent_name, opponent_turn);
+    }
     Ok(())
 }
 
-fn mcts_turn(game: &TicTacToe, state: &Array2<i8>, mcts_player: i8) -> Result<Array2<i8>> {
-    let player_as_char = if mcts_player == 1 { "X" } else { "O" };
-    print!("MCTS turn, playing as '{}':", player_as_char);
+/// Searches the best move using the tree of the previous turn if there is one, advanced past the
+/// player's last move so the statistics of the previous searches carry over
+fn mcts_turn<G: Playable>(
+    config: &MctsConfig,
+    game: &G,
+    tree: &mut Option<ParallelMcts<G>>,
+    position: &mut Position<G>,
+) -> Result<()> {
+    print!("MCTS turn, playing as '{}':", position.to_move());
+
+    if let (Some(tree), Some((_, last_action))) = (tree.as_mut(), position.history().last()) {
+        tree.advance(last_action);
+    }
+
+    // A tree left over from a previous game can not be reused
+    let tree = match tree {
+        Some(tree) if tree.root_position() == position => tree,
+        _ => tree.insert(ParallelMcts::new(config.clone(), game.clone(), position)),
+    };
+
+    let best_action = tree.search();
+    let analysis = tree.search_result();
+    tree.advance(best_action);
 
-    let args: HashMap<&str, f32> =
-        HashMap::from([(("C"), f32::sqrt(2.0)), (("num_searches"), 1000.0)]);
-    let mut tree = Mcts::new(args.clone(), TicTacToe::init(), state, mcts_player);
-    let state = game.apply_move(state, mcts_player, tree.search());
-    game.print_state(&state)?;
-    Ok(state)
+    position.play(game, best_action);
+    game.print(position.state())?;
+    if let Some(statistics) = analysis.best_action() {
+        println!(
+            "MCTS expects a value of {:.2} after {} visits, principal variation: {:?}",
+            statistics.mean_value, statistics.visit_count, analysis.principal_variation
+        );
+    }
+    Ok(())
 }
 
-fn player_turn(game: &TicTacToe, state: &Array2<i8>, chosen_player: i8) -> Result<Array2<i8>> {
-    let chosen_player_as_char = if chosen_player == 1 { "X" } else { "O" };
-    let legal_moves = game.get_legal_moves(state);
+/// Plays one of the moves the solver has proven to be optimal, chosen at random for some variety
+fn solver_turn(
+    solver: &mut Solver,
+    game: &TicTacToe,
+    position: &mut Position<TicTacToe>,
+) -> Result<()> {
+    print!("Solver turn, playing as '{}':", position.to_move());
+
+    let optimal_moves = solver
+        .solve(position.state(), position.to_move())
+        .optimal_moves;
+    let chosen_move = optimal_moves[rand::random_range(0..optimal_moves.len())];
+    position.play(game, chosen_move);
+    game.print_state(position.state())?;
+    Ok(())
+}
 
-    println!("Valid options: {:?}", legal_moves);
+fn player_turn<G: Playable>(game: &G, position: &mut Position<G>) -> Result<()> {
+    let chosen_player = position.to_move();
+    println!("Valid options: {:?}", game.legal_actions(position.state()));
     print!(
-        "Where do you want to put the {}? (Enter the row and then the column, such as '01') ",
-        chosen_player_as_char
+        "Where do you want to put the {}? ({}) ",
+        chosen_player,
+        game.input_hint()
     );
 
     let chosen_action = loop {
         let chosen_action = loop {
             let input = get_input()?;
-            if let [chosen_row, chosen_column] = input.chars().collect::<Vec<_>>()[..] {
-                if let (Some(row), Some(col)) =
-                    (chosen_row.to_digit(10), chosen_column.to_digit(10))
-                {
-                    if row <= 2 && col <= 2 {
-                        break (row as usize, col as usize);
-                    }
-                }
+            match game.parse_action(&input) {
+                Ok(action) => break action,
+                Err(error) => print!("Invalid syntax (\"{}\"), {}: ", input, error),
             }
-
-            print!(
-                "Invalid syntax (\"{}\"), please only provide 2 numbers, between 0 and 2: ",
-                input
-            );
         };
-        if !legal_moves.contains(&chosen_action) {
-            print!(
-                "Illegal move: {:?}, position already taken! ",
-                chosen_action
-            );
-        } else {
-            break chosen_action;
+        match game.try_apply(position.state(), chosen_player, chosen_action) {
+            Ok(_) => break chosen_action,
+            Err(error) => print!(
+                "Illegal move: {}, please choose one of the valid options! ",
+                error
+            ),
         }
     };
 
+    position.play(game, chosen_action);
     print!(
         "Chosen location for '{}': {:?}:",
-        chosen_player_as_char, chosen_action
+        chosen_player, chosen_action
     );
+    game.print(position.state())?;
+
+    Ok(())
+}
 
-    let state = game.apply_move(state, chosen_player, chosen_action);
-    game.print_state(&state)?;
+/// Parses a row and column given as two numbers separated by a space, such as '1 12',
+/// or as two single digits, such as '12'
+fn parse_position(input: &str) -> Option<(usize, usize)> {
+    let input = input.trim();
+    let numbers: Vec<usize> = if input.contains(char::is_whitespace) {
+        input
+            .split_whitespace()
+            .map(|number| number.parse().ok())
+            .collect::<Option<_>>()?
+    } else {
+        input
+            .chars()
+            .map(|digit| digit.to_digit(10).map(|digit| digit as usize))
+            .collect::<Option<_>>()?
+    };
 
-    Ok(state.clone())
+    match numbers[..] {
+        [row, column] => Some((row, column)),
+        _ => None,
+    }
 }
 
-fn rematch_option() -> Result<()> {
+fn rematch_option() -> Result<bool> {
     print!("Would you like a rematch? ");
     let rematch = loop {
         let input = get_input()?;
@@ -230,8 +574,5 @@ fn rematch_option() -> Result<()> {
             input
         );
     };
-    if rematch == 'y' {
-        player_vs_mcts()?;
-    }
-    Ok(())
+    Ok(rematch == 'y')
 }
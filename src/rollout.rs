@@ -0,0 +1,141 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+
+use crate::{
+    config::Rollout,
+    games::{Game, Outcome},
+};
+
+/// Chooses the moves of the rollouts of the MCTS, which play a game out from a newly expanded node
+/// to estimate its value
+pub trait RolloutPolicy<G: Game>: Send + Sync {
+    /// Chooses the action the given player plays next in the given state, out of the legal
+    /// actions, which are never empty
+    fn choose_action(
+        &self,
+        game: &G,
+        state: &G::State,
+        player: G::Player,
+        legal_actions: &[G::Action],
+        rng: &mut StdRng,
+    ) -> G::Action;
+
+    /// The number of moves after which the rollout stops and the state is valued with
+    /// `Game::evaluate` instead, None to play until the game is over
+    fn max_depth(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Creates the rollout policy chosen in the config
+pub fn from_config<G: Game>(rollout: Rollout) -> Box<dyn RolloutPolicy<G>> {
+    match rollout {
+        Rollout::Uniform => Box::new(UniformRollout),
+        Rollout::WinOrBlock => Box::new(WinOrBlockRollout),
+        Rollout::EpsilonGreedy { epsilon } => Box::new(EpsilonGreedyRollout { epsilon }),
+        Rollout::Truncated { depth } => Box::new(TruncatedRollout { depth }),
+    }
+}
+
+/// Plays uniformly random moves
+pub struct UniformRollout;
+
+impl<G: Game> RolloutPolicy<G> for UniformRollout {
+    fn choose_action(
+        &self,
+        _game: &G,
+        _state: &G::State,
+        _player: G::Player,
+        legal_actions: &[G::Action],
+        rng: &mut StdRng,
+    ) -> G::Action {
+        legal_actions[rng.random_range(0..legal_actions.len())]
+    }
+}
+
+/// Plays a winning move when there is one, otherwise blocks a move the opponent would win with
+/// if it were their turn, otherwise plays a random move
+pub struct WinOrBlockRollout;
+
+impl<G: Game> RolloutPolicy<G> for WinOrBlockRollout {
+    fn choose_action(
+        &self,
+        game: &G,
+        state: &G::State,
+        player: G::Player,
+        legal_actions: &[G::Action],
+        rng: &mut StdRng,
+    ) -> G::Action {
+        let wins = |player: G::Player, action: G::Action| {
+            game.outcome(&game.apply(state, player, action), player) == Outcome::Win(player)
+        };
+
+        let opponent = game.opponent(player);
+        legal_actions
+            .iter()
+            .copied()
+            .find(|&action| wins(player, action))
+            .or_else(|| {
+                legal_actions
+                    .iter()
+                    .copied()
+                    .find(|&action| wins(opponent, action))
+            })
+            .unwrap_or_else(|| {
+                UniformRollout.choose_action(game, state, player, legal_actions, rng)
+            })
+    }
+}
+
+/// Plays the move after which `Game::evaluate` values the state best for the player, or a random
+/// move with a chance of `epsilon`. Ties go to the first of the best moves
+pub struct EpsilonGreedyRollout {
+    pub epsilon: f32,
+}
+
+impl<G: Game> RolloutPolicy<G> for EpsilonGreedyRollout {
+    fn choose_action(
+        &self,
+        game: &G,
+        state: &G::State,
+        player: G::Player,
+        legal_actions: &[G::Action],
+        rng: &mut StdRng,
+    ) -> G::Action {
+        if rng.random::<f32>() < self.epsilon {
+            return UniformRollout.choose_action(game, state, player, legal_actions, rng);
+        }
+
+        let mut best = (legal_actions[0], f32::NEG_INFINITY);
+        for &action in legal_actions {
+            let value = game.evaluate(&game.apply(state, player, action), player);
+            if value > best.1 {
+                best = (action, value);
+            }
+        }
+        best.0
+    }
+}
+
+/// Plays random moves, but stops after `depth` moves and values the state with `Game::evaluate`,
+/// trading the accuracy of the rollouts for more searches
+pub struct TruncatedRollout {
+    pub depth: usize,
+}
+
+impl<G: Game> RolloutPolicy<G> for TruncatedRollout {
+    fn choose_action(
+        &self,
+        game: &G,
+        state: &G::State,
+        player: G::Player,
+        legal_actions: &[G::Action],
+        rng: &mut StdRng,
+    ) -> G::Action {
+        UniformRollout.choose_action(game, state, player, legal_actions, rng)
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        Some(self.depth)
+    }
+}
@@ -0,0 +1,176 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand_distr::{Beta, Distribution};
+
+use crate::config::Selection;
+
+/// What the search knows about a child of a fully expanded node when it selects one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChildStatistics {
+    /// The visits of the child, counting the searches still passing through it
+    pub visit_count: f32,
+    /// The average value from the perspective of the player choosing the child, 0.0 when it has
+    /// not been visited yet
+    pub mean_value: f32,
+    /// The average of the squared values, used for the variance of the values
+    pub mean_squared_value: f32,
+    /// The probability of the child being chosen before searching
+    pub prior: f32,
+}
+
+impl ChildStatistics {
+    fn variance(&self) -> f32 {
+        (self.mean_squared_value - self.mean_value * self.mean_value).max(0.0)
+    }
+}
+
+/// Chooses which child the selection phase of the MCTS descends into
+pub trait SelectionPolicy: Send + Sync {
+    /// Returns the index of the chosen child, out of children that are never empty
+    fn select(
+        &self,
+        parent_visit_count: f32,
+        children: &[ChildStatistics],
+        rng: &mut StdRng,
+    ) -> usize;
+}
+
+/// Creates the selection policy chosen in the config
+pub fn from_config(selection: Selection, exploration_constant: f32) -> Box<dyn SelectionPolicy> {
+    match selection {
+        Selection::Ucb1 => Box::new(Ucb1 {
+            exploration_constant,
+        }),
+        Selection::Ucb1Tuned => Box::new(Ucb1Tuned {
+            exploration_constant,
+        }),
+        Selection::Puct => Box::new(Puct {
+            exploration_constant,
+        }),
+        Selection::Thompson => Box::new(Thompson),
+        Selection::EpsilonGreedy { epsilon } => Box::new(EpsilonGreedy { epsilon }),
+    }
+}
+
+/// The UCB of a child with mean value `q` and `n` visits, whose parent has `N` visits, using the
+/// exploration constant `C`
+#[allow(non_snake_case)]
+pub fn ucb(q: f32, n: f32, N: f32, C: f32) -> f32 {
+    q + C * f32::sqrt(N.ln_1p() / n)
+}
+
+/// The index of the child with the highest score, ties go to the first child
+fn highest_score(
+    children: &[ChildStatistics],
+    mut score: impl FnMut(&ChildStatistics) -> f32,
+) -> usize {
+    let mut best = (0, f32::MIN);
+    for (index, child) in children.iter().enumerate() {
+        let score = score(child);
+        if score > best.1 {
+            best = (index, score);
+        }
+    }
+    best.0
+}
+
+/// Upper Confidence Bound 1, unvisited children are always selected first
+pub struct Ucb1 {
+    pub exploration_constant: f32,
+}
+
+impl SelectionPolicy for Ucb1 {
+    fn select(
+        &self,
+        parent_visit_count: f32,
+        children: &[ChildStatistics],
+        _: &mut StdRng,
+    ) -> usize {
+        highest_score(children, |child| {
+            ucb(
+                child.mean_value,
+                child.visit_count,
+                parent_visit_count,
+                self.exploration_constant,
+            )
+        })
+    }
+}
+
+/// UCB1-Tuned, explores children with a small variance in their values less than UCB1 does.
+/// The variance is bounded by that of values between 0.0 and 1.0, so the values are scaled to that
+/// range for the formula and the score is scaled back
+pub struct Ucb1Tuned {
+    pub exploration_constant: f32,
+}
+
+impl SelectionPolicy for Ucb1Tuned {
+    fn select(
+        &self,
+        parent_visit_count: f32,
+        children: &[ChildStatistics],
+        _: &mut StdRng,
+    ) -> usize {
+        let log_visits = parent_visit_count.ln_1p();
+
+        highest_score(children, |child| {
+            let n = child.visit_count;
+            let variance_bound = child.variance() / 4.0 + (2.0 * log_visits / n).sqrt();
+            let exploration = (log_visits / n * variance_bound.min(0.25)).sqrt();
+            child.mean_value + 2.0 * self.exploration_constant * exploration
+        })
+    }
+}
+
+/// The PUCT formula of AlphaZero, weighing the exploration of a child by its prior
+pub struct Puct {
+    pub exploration_constant: f32,
+}
+
+impl SelectionPolicy for Puct {
+    fn select(
+        &self,
+        parent_visit_count: f32,
+        children: &[ChildStatistics],
+        _: &mut StdRng,
+    ) -> usize {
+        highest_score(children, |child| {
+            child.mean_value
+                + self.exploration_constant * child.prior * parent_visit_count.sqrt()
+                    / (1.0 + child.visit_count)
+        })
+    }
+}
+
+/// Thompson sampling, scores every child by a sample of the Beta posterior of its win rate. A
+/// value of 1.0 counts as a win, -1.0 as a loss and a draw as half of both
+pub struct Thompson;
+
+impl SelectionPolicy for Thompson {
+    fn select(&self, _: f32, children: &[ChildStatistics], rng: &mut StdRng) -> usize {
+        highest_score(children, |child| {
+            let wins = (child.visit_count * (1.0 + child.mean_value) / 2.0).max(0.0);
+            let losses = (child.visit_count - wins).max(0.0);
+
+            // Both parameters are at least 1.0, so the distribution is always valid
+            Beta::new(1.0 + wins, 1.0 + losses)
+                .map(|posterior| posterior.sample(&mut *rng))
+                .unwrap_or(0.0)
+        })
+    }
+}
+
+/// Selects a random child with a chance of `epsilon`, otherwise the child with the best mean value
+pub struct EpsilonGreedy {
+    pub epsilon: f32,
+}
+
+impl SelectionPolicy for EpsilonGreedy {
+    fn select(&self, _: f32, children: &[ChildStatistics], rng: &mut StdRng) -> usize {
+        if rng.random::<f32>() < self.epsilon {
+            return rng.random_range(0..children.len());
+        }
+
+        highest_score(children, |child| child.mean_value)
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::{
+    games::{Board, Move, Player, TicTacToe},
+    symmetry,
+};
+
+/// The game-theoretic value of a position for the player to move, together with every move that
+/// achieves that value
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolvedPosition {
+    /// 1 for a win, 0 for a draw and -1 for a loss, assuming perfect play from both sides
+    pub value: i8,
+    pub optimal_moves: Vec<Move>,
+}
+
+/// Whether a memoized value is exact or only a bound, because alpha-beta cut the search short
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+/// Solves tic-tac-toe positions exactly using negamax with alpha-beta pruning, remembering the
+/// value of every position it has searched so following searches can reuse them. Symmetric
+/// positions have the same value, so they are remembered by their canonical form, which is hashed
+/// by its Zobrist hash and compared cell by cell so a hash collision can't return a wrong value
+pub struct Solver {
+    game: TicTacToe,
+    memo: HashMap<(Board, Player), (i8, Bound)>,
+}
+
+impl Solver {
+    pub fn new(game: TicTacToe) -> Solver {
+        Solver {
+            game,
+            memo: HashMap::new(),
+        }
+    }
+
+    /// The number of positions remembered
+    #[allow(dead_code)] // Used inside tests
+    pub fn memo_size(&self) -> usize {
+        self.memo.len()
+    }
+
+    /// Solves the given state where the given player is about to move
+    pub fn solve(&mut self, state: &Board, player: Player) -> SolvedPosition {
+        if self.game.check_win(state, player.opponent()) {
+            return SolvedPosition {
+                value: -1,
+                optimal_moves: vec![],
+            };
+        }
+
+        let mut value = i8::MIN;
+        let mut optimal_moves = vec![];
+
+        for action in self.game.get_legal_moves(state) {
+            let next_state = self.game.apply_move(state, player, action);
+            let action_value = -self.negamax(&next_state, player.opponent(), -1, 1);
+
+            if action_value > value {
+                value = action_value;
+                optimal_moves.clear();
+            }
+            if action_value == value {
+                optimal_moves.push(action);
+            }
+        }
+
+        SolvedPosition {
+            // A full board without a winner is a draw
+            value: if optimal_moves.is_empty() { 0 } else { value },
+            optimal_moves,
+        }
+    }
+
+    /// Returns the value of the given state for the player about to move, within alpha and beta
+    fn negamax(&mut self, state: &Board, player: Player, mut alpha: i8, mut beta: i8) -> i8 {
+        // The opponent made the last move, so they are the only one that could have won
+        if self.game.check_win(state, player.opponent()) {
+            return -1;
+        }
+
+        let legal_moves = self.game.get_legal_moves(state);
+        if legal_moves.is_empty() {
+            return 0;
+        }
+
+        let key = (Board::from(symmetry::canonical_form(state).0), player);
+        if let Some(&(value, bound)) = self.memo.get(&key) {
+            match bound {
+                Bound::Exact => return value,
+                Bound::Lower => alpha = alpha.max(value),
+                Bound::Upper => beta = beta.min(value),
+            }
+            if alpha >= beta {
+                return value;
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut value = -1;
+
+        for action in legal_moves {
+            let next_state = self.game.apply_move(state, player, action);
+            value = value.max(-self.negamax(&next_state, player.opponent(), -beta, -alpha));
+            alpha = alpha.max(value);
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if value <= original_alpha {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.memo.insert(key, (value, bound));
+
+        value
+    }
+}
@@ -0,0 +1,142 @@
+use ndarray::Array2;
+
+/// One of the ways a board can be rotated or reflected onto itself, a square board has all 8 of
+/// them, other boards only the 4 that keep their shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Identity,
+    /// A quarter turn clockwise
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors the top and bottom rows
+    FlipRows,
+    /// Mirrors the left and right columns
+    FlipColumns,
+    /// Mirrors along the diagonal from the top left to the bottom right
+    Transpose,
+    /// Mirrors along the diagonal from the top right to the bottom left
+    AntiTranspose,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipRows,
+        Symmetry::FlipColumns,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// The symmetries of a board of the given size, only a square board can be turned a quarter
+    /// or mirrored along its diagonals
+    pub fn of_board(row_count: usize, column_count: usize) -> Vec<Symmetry> {
+        if row_count == column_count {
+            Symmetry::ALL.to_vec()
+        } else {
+            vec![
+                Symmetry::Identity,
+                Symmetry::Rotate180,
+                Symmetry::FlipRows,
+                Symmetry::FlipColumns,
+            ]
+        }
+    }
+
+    /// The symmetry that undoes this one
+    #[allow(dead_code)] // Used inside tests
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+
+    /// Returns where the given position ends up on a board of the given size
+    pub fn apply_to_position(
+        self,
+        (row, column): (usize, usize),
+        row_count: usize,
+        column_count: usize,
+    ) -> (usize, usize) {
+        let last_row = row_count - 1;
+        let last_column = column_count - 1;
+
+        match self {
+            Symmetry::Identity => (row, column),
+            Symmetry::Rotate90 => (column, last_row - row),
+            Symmetry::Rotate180 => (last_row - row, last_column - column),
+            Symmetry::Rotate270 => (last_column - column, row),
+            Symmetry::FlipRows => (last_row - row, column),
+            Symmetry::FlipColumns => (row, last_column - column),
+            Symmetry::Transpose => (column, row),
+            Symmetry::AntiTranspose => (last_column - column, last_row - row),
+        }
+    }
+
+    pub fn apply_to_state(self, state: &Array2<i8>) -> Array2<i8> {
+        let (row_count, column_count) = state.dim();
+        let mut new_state = Array2::zeros(state.raw_dim());
+
+        for (position, &cell) in state.indexed_iter() {
+            let (row, column) = self.apply_to_position(position, row_count, column_count);
+            new_state[[row, column]] = cell;
+        }
+
+        new_state
+    }
+}
+
+/// Returns the canonical form of the given board, the same for all boards that are symmetric to
+/// each other, and the symmetry that maps the board onto it. Positions on the canonical board
+/// can be mapped back with the inverse of that symmetry
+pub fn canonical_form(state: &Array2<i8>) -> (Array2<i8>, Symmetry) {
+    let (row_count, column_count) = state.dim();
+    let mut canonical = (state.clone(), Symmetry::Identity);
+
+    for symmetry in Symmetry::of_board(row_count, column_count) {
+        let symmetric_state = symmetry.apply_to_state(state);
+        // The smallest board, comparing the cells row by row, is the canonical one
+        if symmetric_state.iter().lt(canonical.0.iter()) {
+            canonical = (symmetric_state, symmetry);
+        }
+    }
+
+    canonical
+}
+
+/// Keeps the first position of every group of positions that are symmetric to each other on the
+/// given board, as playing any of them leads to the same game
+pub fn distinct_positions(
+    state: &Array2<i8>,
+    symmetries: &[Symmetry],
+    positions: Vec<(usize, usize)>,
+) -> Vec<(usize, usize)> {
+    let (row_count, column_count) = state.dim();
+    let board_symmetries: Vec<Symmetry> = symmetries
+        .iter()
+        .copied()
+        .filter(|symmetry| symmetry.apply_to_state(state) == state)
+        .collect();
+
+    let mut distinct_positions: Vec<(usize, usize)> = vec![];
+    for position in positions {
+        let is_symmetric_to_earlier_position = board_symmetries.iter().any(|symmetry| {
+            distinct_positions.contains(&symmetry.apply_to_position(
+                position,
+                row_count,
+                column_count,
+            ))
+        });
+
+        if !is_symmetric_to_earlier_position {
+            distinct_positions.push(position);
+        }
+    }
+
+    distinct_positions
+}
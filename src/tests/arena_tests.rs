@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod Arena_tests {
+    use rstest::rstest;
+    use std::thread;
+
+    use crate::arena::Arena;
+
+    #[rstest]
+    #[case::first_segment(10)]
+    #[case::several_segments(5000)]
+    fn items_keep_their_index(#[case] len: usize) {
+        let arena = Arena::new();
+        for item in 0..len {
+            assert_eq!(arena.push_with(|index| index * 2), item);
+        }
+
+        assert_eq!(arena.len(), len);
+        assert_eq!(arena[len - 1], (len - 1) * 2);
+        assert_eq!(arena.get(len), None);
+        assert_eq!(
+            arena.into_vec(),
+            (0..len).map(|item| item * 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn threads_can_push_at_the_same_time() {
+        let arena = Arena::from(vec![0]);
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..1000 {
+                        let index = arena.push_with(|index| index);
+                        assert_eq!(arena[index], index);
+                    }
+                });
+            }
+        });
+
+        let items = arena.into_vec();
+        assert_eq!(items, (0..4001).collect::<Vec<_>>());
+    }
+}
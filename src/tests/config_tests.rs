@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod MctsConfig_tests {
+    use rstest::rstest;
+    use std::time::Duration;
+
+    use anyhow::{Ok, Result};
+
+    use crate::config::MctsConfig;
+
+    #[test]
+    fn builder_defaults_to_default_config() -> Result<()> {
+        let config = MctsConfig::builder().build()?;
+        assert_eq!(config, MctsConfig::default());
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::zero_searches(0, 1.0)]
+    #[case::negative_C(1000, -1.0)]
+    #[case::infinite_C(1000, f32::INFINITY)]
+    #[case::NaN_C(1000, f32::NAN)]
+    fn builder_rejects_invalid_values(#[case] num_searches: u32, #[case] C: f32) {
+        let config = MctsConfig::builder()
+            .num_searches(num_searches)
+            .exploration_constant(C)
+            .build();
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn config_loads_from_toml_and_json() -> Result<()> {
+        let expected = MctsConfig::builder()
+            .num_searches(250)
+            .exploration_constant(1.5)
+            .build()?;
+
+        let from_toml = MctsConfig::from_toml("num_searches = 250\nexploration_constant = 1.5")?;
+        let from_json =
+            MctsConfig::from_json(r#"{ "num_searches": 250, "exploration_constant": 1.5 }"#)?;
+
+        assert_eq!(from_toml, expected);
+        assert_eq!(from_json, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn default_search_limit_only_applies_without_other_limits() -> Result<()> {
+        let config = MctsConfig::from_toml("max_duration_ms = 500")?;
+
+        assert_eq!(config.num_searches(), None);
+        assert_eq!(config.max_duration(), Some(Duration::from_millis(500)));
+        assert_eq!(MctsConfig::default().num_searches(), Some(1000));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::missing_fields_use_defaults("", true)]
+    #[case::zero_duration("max_duration_ms = 0", false)]
+    #[case::single_node("max_nodes = 1", false)]
+    #[case::combined_limits("num_searches = 10\nmax_duration_ms = 100\nmax_nodes = 500", true)]
+    #[case::zero_searches("num_searches = 0", false)]
+    #[case::zero_threads("num_threads = 0", false)]
+    #[case::multiple_threads("num_threads = 4", true)]
+    #[case::tree_parallelism("num_threads = 4\nparallelism = \"tree\"", true)]
+    #[case::unknown_parallelism("parallelism = \"leaf\"", false)]
+    #[case::transpositions("transpositions = true", true)]
+    #[case::merge_symmetric_moves("merge_symmetric_moves = true", true)]
+    #[case::solver("solver = true", true)]
+    #[case::rave_equivalence("rave = { schedule = \"equivalence\", equivalence = 300.0 }", true)]
+    #[case::rave_minimum_error("rave = { schedule = \"minimum_error\", bias = 0.1 }", true)]
+    #[case::zero_rave_equivalence(
+        "rave = { schedule = \"equivalence\", equivalence = 0.0 }",
+        false
+    )]
+    #[case::negative_rave_bias("rave = { schedule = \"minimum_error\", bias = -0.1 }", false)]
+    #[case::unknown_rave_schedule("rave = { schedule = \"linear\" }", false)]
+    #[case::win_or_block_rollout("rollout = { policy = \"win_or_block\" }", true)]
+    #[case::epsilon_greedy_rollout(
+        "rollout = { policy = \"epsilon_greedy\", epsilon = 0.1 }",
+        true
+    )]
+    #[case::truncated_rollout("rollout = { policy = \"truncated\", depth = 4 }", true)]
+    #[case::epsilon_above_one("rollout = { policy = \"epsilon_greedy\", epsilon = 1.5 }", false)]
+    #[case::missing_rollout_depth("rollout = { policy = \"truncated\" }", false)]
+    #[case::ucb1_tuned_selection("selection = { policy = \"ucb1_tuned\" }", true)]
+    #[case::thompson_selection("selection = { policy = \"thompson\" }", true)]
+    #[case::epsilon_greedy_selection(
+        "selection = { policy = \"epsilon_greedy\", epsilon = 0.1 }",
+        true
+    )]
+    #[case::negative_selection_epsilon(
+        "selection = { policy = \"epsilon_greedy\", epsilon = -0.1 }",
+        false
+    )]
+    #[case::max_value_final_move("final_move = { strategy = \"max_value\" }", true)]
+    #[case::max_robust_final_move(
+        "final_move = { strategy = \"max_robust\", max_extra_searches = 500 }",
+        true
+    )]
+    #[case::secure_child_final_move(
+        "final_move = { strategy = \"secure_child\", confidence = 1.0 }",
+        true
+    )]
+    #[case::temperature_final_move(
+        "final_move = { strategy = \"temperature\", temperature = 1.0 }",
+        true
+    )]
+    #[case::zero_temperature(
+        "final_move = { strategy = \"temperature\", temperature = 0.0 }",
+        false
+    )]
+    #[case::unknown_field("C = 1.0", false)]
+    fn config_validates_toml(#[case] contents: &str, #[case] is_valid: bool) {
+        assert_eq!(MctsConfig::from_toml(contents).is_ok(), is_valid);
+    }
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod FinalMove_tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::config::{FinalMove, MctsConfig};
+    use crate::final_move::{choose_action, is_robust};
+    use crate::games::{Player, Position, TicTacToe};
+    use crate::mcts::{ActionStatistics, Mcts, Proof};
+    use crate::parallel::ParallelMcts;
+
+    #[rstest]
+    #[case::max_visits(FinalMove::MaxVisits, 'b')]
+    #[case::max_robust(FinalMove::MaxRobust { max_extra_searches: 100 }, 'b')]
+    #[case::max_value(FinalMove::MaxValue, 'c')]
+    #[case::secure_child(FinalMove::SecureChild { confidence: 1.0 }, 'a')]
+    #[case::cold_temperature(FinalMove::Temperature { temperature: 0.01 }, 'b')]
+    fn strategies_choose_their_action(#[case] strategy: FinalMove, #[case] expected: char) {
+        let actions = [
+            statistics('a', 400, 0.5),
+            statistics('b', 500, 0.3),
+            statistics('c', 4, 0.9),
+            statistics('d', 0, 0.0),
+        ];
+
+        let action = choose_action(strategy, &actions, &mut StdRng::seed_from_u64(42));
+        assert_eq!(action, Some(expected));
+    }
+
+    #[test]
+    fn temperature_samples_every_visited_action() {
+        let actions = [
+            statistics('a', 400, 0.5),
+            statistics('b', 500, 0.3),
+            statistics('c', 100, 0.9),
+            statistics('d', 0, 0.0),
+        ];
+        let strategy = FinalMove::Temperature { temperature: 1.0 };
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let chosen: HashSet<char> = (0..200)
+            .filter_map(|_| choose_action(strategy, &actions, &mut rng))
+            .collect();
+        assert_eq!(chosen, HashSet::from(['a', 'b', 'c']));
+    }
+
+    #[test]
+    fn no_action_is_chosen_without_statistics() {
+        let actions: [ActionStatistics<char>; 0] = [];
+        let mut rng = StdRng::seed_from_u64(42);
+
+        assert_eq!(
+            choose_action(FinalMove::MaxVisits, &actions, &mut rng),
+            None
+        );
+        assert!(is_robust(&actions));
+    }
+
+    #[rstest]
+    #[case::agreeing(vec![statistics('a', 500, 0.5), statistics('b', 400, 0.3)], true)]
+    #[case::disagreeing(vec![statistics('a', 500, 0.3), statistics('b', 400, 0.5)], false)]
+    #[case::unvisited(vec![statistics('a', 500, 0.3), statistics('b', 0, 0.0)], true)]
+    fn robust_when_the_most_visited_action_has_the_best_value(
+        #[case] actions: Vec<ActionStatistics<char>>,
+        #[case] robust: bool,
+    ) {
+        assert_eq!(is_robust(&actions), robust);
+    }
+
+    #[test]
+    fn max_robust_search_continues_until_robust() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let config = MctsConfig::builder()
+            .seed(42)
+            .num_searches(50)
+            .final_move(FinalMove::MaxRobust {
+                max_extra_searches: 1000,
+            })
+            .build()?;
+
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state, Player::X),
+        );
+        let action = tree.search();
+
+        let result = tree.search_result();
+        assert!(tree.root_visit_count() >= 50 && tree.root_visit_count() <= 1050);
+        assert!(is_robust(&result.actions) || tree.root_visit_count() == 1050);
+        assert_eq!(Some(action), result.best_action().map(|best| best.action));
+        Ok(())
+    }
+
+    #[test]
+    fn temperature_makes_self_play_diverse() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+
+        let mut first_moves = HashSet::new();
+        for seed in 0..10 {
+            let config = MctsConfig::builder()
+                .seed(seed)
+                .num_searches(200)
+                .final_move(FinalMove::Temperature { temperature: 1.0 })
+                .build()?;
+            let mut tree = ParallelMcts::new(
+                config,
+                TicTacToe::init(),
+                &Position::new(&game, state.clone(), Player::X),
+            );
+            first_moves.insert(tree.search());
+        }
+
+        assert!(first_moves.len() > 1);
+        Ok(())
+    }
+
+    fn statistics(action: char, visit_count: u32, mean_value: f32) -> ActionStatistics<char> {
+        ActionStatistics {
+            action,
+            visit_count,
+            mean_value,
+            ucb: 0.0,
+            prior: 0.25,
+            proof: Proof::Unproven,
+        }
+    }
+}
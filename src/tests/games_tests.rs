@@ -0,0 +1,603 @@
+#[cfg(test)]
+mod TicTacToe_tests {
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::games::Player::{O, X};
+    use crate::games::{Move, Player, TicTacToe};
+
+    #[rstest]
+    #[case::row(vec![(1, 0, player), (1, 1, player), (1, 2, player)], true)]
+    #[case::column(vec![(0, 3, player), (1, 3, player), (2, 3, player)], true)]
+    #[case::diagonal(vec![(1, 1, player), (2, 2, player), (3, 3, player)], true)]
+    #[case::inversed_diagonal(vec![(0, 3, player), (1, 2, player), (2, 1, player)], true)]
+    #[case::two_in_a_row(vec![(3, 2, player), (3, 3, player)], false)]
+    #[case::interrupted(vec![(0, 0, player), (0, 1, player), (0, 2, player.opponent()), (0, 3, player)], false)]
+    #[case::wrapped_around(vec![(0, 3, player), (1, 0, player), (1, 1, player)], false)]
+    fn four_by_four_with_three_in_a_row(
+        #[values(X, O)] player: Player,
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] is_win: bool,
+    ) -> Result<()> {
+        let game = TicTacToe::new(4, 4, 3)?;
+        let state = game.create_state(player_coordinates);
+
+        assert_eq!(game.check_win(&state, player), is_win);
+        assert!(!game.check_win(&state, player.opponent()));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::five_in_a_row(vec![(7, 3, X), (7, 4, X), (7, 5, X), (7, 6, X), (7, 7, X)], true)]
+    #[case::four_in_a_row(vec![(7, 3, X), (7, 4, X), (7, 5, X), (7, 6, X)], false)]
+    #[case::edge_diagonal(vec![(10, 14, X), (11, 13, X), (12, 12, X), (13, 11, X), (14, 10, X)], true)]
+    fn gomoku_with_five_in_a_row(
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] is_win: bool,
+    ) -> Result<()> {
+        let game = TicTacToe::new(15, 15, 5)?;
+        let state = game.create_state(player_coordinates);
+
+        assert_eq!(game.check_win(&state, X), is_win);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::occupied_cell(vec![(1, 1, X)], O, Move(1, 1), "Cell (1, 1) is already taken by X")]
+    #[case::out_of_bounds(vec![], X, Move(3, 0), "Cell (3, 0) is outside of the 3x3 board")]
+    #[case::game_over(vec![(0, 0, X), (1, 0, O), (0, 1, X), (1, 1, O), (0, 2, X)], O, Move(2, 2), "The game is already over")]
+    #[case::wrong_player(vec![(1, 1, X)], X, Move(0, 0), "It is O's turn, not X's")]
+    fn invalid_moves_are_explained(
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] player: Player,
+        #[case] action: Move,
+        #[case] error: &str,
+    ) {
+        let game = TicTacToe::init();
+        let state = game.create_state(player_coordinates);
+
+        let result = game.try_apply_move(&state, player, action);
+        assert!(result.is_err_and(|result_error| result_error.to_string() == error));
+    }
+
+    #[test]
+    fn valid_moves_are_applied() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(1, 1, X)]);
+
+        assert_eq!(
+            game.try_apply_move(&state, O, Move(0, 0))?,
+            game.apply_move(&state, O, Move(0, 0))
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::no_rows(0, 3, 3)]
+    #[case::no_win_length(3, 3, 0)]
+    #[case::win_length_too_long(3, 4, 5)]
+    fn invalid_boards_are_rejected(
+        #[case] row_count: usize,
+        #[case] column_count: usize,
+        #[case] win_length: usize,
+    ) {
+        assert!(TicTacToe::new(row_count, column_count, win_length).is_err());
+    }
+}
+
+#[cfg(test)]
+mod ConnectFour_tests {
+    use rstest::rstest;
+
+    use crate::games::ConnectFour;
+    use crate::games::Player::{self, O, X};
+
+    #[test]
+    fn pieces_fall_to_the_lowest_empty_row() {
+        let game = ConnectFour::init();
+        let state = game.create_state(vec![(3, X), (3, O), (4, X)]);
+
+        assert_eq!(state[[5, 3]], 1);
+        assert_eq!(state[[4, 3]], -1);
+        assert_eq!(state[[5, 4]], 1);
+        assert_eq!(state.iter().filter(|&&cell| cell != 0).count(), 3);
+    }
+
+    #[test]
+    fn full_columns_are_not_legal() {
+        let game = ConnectFour::init();
+        let state = game.create_state(
+            (0..6)
+                .map(|row| (2, if row % 2 == 0 { X } else { O }))
+                .collect(),
+        );
+
+        assert!(!game.get_legal_moves(&state).contains(&2));
+        assert_eq!(game.get_legal_moves(&state).len(), 6);
+        assert!(game
+            .try_apply_move(&state, X, 2)
+            .is_err_and(|error| error.to_string() == "Column 2 is already full"));
+    }
+
+    #[test]
+    fn invalid_drops_are_explained() {
+        let game = ConnectFour::init();
+        let state = game.create_state(vec![(3, X)]);
+
+        assert!(game
+            .try_apply_move(&state, O, 7)
+            .is_err_and(|error| error.to_string()
+                == "Column 7 is outside of the board, which has 7 columns"));
+        assert!(game
+            .try_apply_move(&state, X, 3)
+            .is_err_and(|error| error.to_string() == "It is O's turn, not X's"));
+        assert!(game.try_apply_move(&state, O, 3).is_ok());
+    }
+
+    #[rstest]
+    #[case::row(vec![(0, player), (1, player), (2, player), (3, player)], true)]
+    #[case::column(vec![(6, player), (6, player), (6, player), (6, player)], true)]
+    #[case::diagonal(vec![(0, player), (1, player.opponent()), (1, player), (2, player.opponent()), (2, player.opponent()), (2, player), (3, player.opponent()), (3, player.opponent()), (3, player.opponent()), (3, player)], true)]
+    #[case::three_in_a_row(vec![(0, player), (1, player), (2, player)], false)]
+    #[case::interrupted(vec![(0, player), (1, player), (2, player.opponent()), (3, player), (4, player)], false)]
+    fn four_in_a_row_wins(
+        #[values(X, O)] player: Player,
+        #[case] player_columns: Vec<(usize, Player)>,
+        #[case] is_win: bool,
+    ) {
+        let game = ConnectFour::init();
+        let state = game.create_state(player_columns);
+
+        assert_eq!(game.check_win(&state, player), is_win);
+        assert!(!game.check_win(&state, player.opponent()));
+    }
+}
+
+#[cfg(test)]
+mod UltimateTicTacToe_tests {
+    use crate::games::Player::{O, X};
+    use crate::games::{Move, Outcome, UltimateTicTacToe};
+
+    #[test]
+    fn played_cell_dictates_next_sub_board() {
+        let game = UltimateTicTacToe::init();
+        let state = game.create_state(vec![(4, 5, X)]);
+
+        let legal_moves = game.get_legal_moves(&state);
+
+        assert_eq!(state.next_sub_board, Some((1, 2)));
+        assert_eq!(legal_moves.len(), 9);
+        assert!(legal_moves
+            .iter()
+            .all(|&Move(row, column)| (3..6).contains(&row) && (6..9).contains(&column)));
+    }
+
+    #[test]
+    fn winning_a_sub_board_claims_it_and_closes_it() {
+        let game = UltimateTicTacToe::init();
+        let state = game.create_state(vec![
+            (1, 1, X),
+            (3, 3, O),
+            (0, 0, X),
+            (0, 2, O),
+            (1, 6, X),
+            (3, 0, O),
+            (2, 2, X),
+        ]);
+
+        assert_eq!(state.meta_board[[0, 0]], 1);
+        assert!(!game.check_win(&state, X));
+
+        // Being sent to the won sub-board allows playing in any open sub-board
+        let state = game.apply_move(&state, O, Move(6, 6));
+        assert_eq!(state.next_sub_board, None);
+        assert!(game
+            .get_legal_moves(&state)
+            .iter()
+            .all(|&Move(row, column)| row >= 3 || column >= 3));
+    }
+
+    #[test]
+    fn moves_outside_of_the_next_sub_board_are_explained() {
+        let game = UltimateTicTacToe::init();
+        let state = game.create_state(vec![(4, 5, X)]);
+
+        assert!(game.try_apply_move(&state, O, Move(0, 0)).is_err_and(
+            |error| error.to_string() == "The move has to be played in sub-board (1, 2)"
+        ));
+        assert!(game.try_apply_move(&state, O, Move(3, 6)).is_ok());
+    }
+
+    #[test]
+    fn winning_three_sub_boards_in_a_row_wins_the_game() {
+        let game = UltimateTicTacToe::init();
+        let mut state = game.get_initial_state();
+        for sub_board in 0..3 {
+            state.meta_board[[sub_board, sub_board]] = O.cell();
+        }
+
+        assert!(game.check_win(&state, O));
+        assert_eq!(game.get_outcome(&state, O), Outcome::Win(O));
+    }
+}
+
+#[cfg(test)]
+mod BitboardTicTacToe_tests {
+    use std::time::{Duration, Instant};
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::config::MctsConfig;
+    use crate::games::Player::{O, X};
+    use crate::games::{BitboardTicTacToe, Game, Move, Player, Position, TicTacToe};
+    use crate::mcts::Mcts;
+
+    #[rstest]
+    #[case::normal(3, 3, 3)]
+    #[case::four_by_four(4, 4, 3)]
+    #[case::rectangle(3, 5, 3)]
+    #[case::full_bitboard(8, 8, 5)]
+    #[case::single_cell_lines(2, 2, 1)]
+    fn plays_the_same_as_the_ndarray_board(
+        #[case] row_count: usize,
+        #[case] column_count: usize,
+        #[case] win_length: usize,
+    ) -> Result<()> {
+        let game = TicTacToe::new(row_count, column_count, win_length)?;
+        let bitboard_game = BitboardTicTacToe::new(row_count, column_count, win_length)?;
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..20 {
+            let mut state = game.initial_state();
+            let mut bitboard_state = bitboard_game.initial_state();
+            let mut player = X;
+            loop {
+                let legal_actions = game.legal_actions(&state);
+                assert_eq!(bitboard_game.legal_actions(&bitboard_state), legal_actions);
+
+                let action = legal_actions[rng.random_range(0..legal_actions.len())];
+                state = game.apply(&state, player, action);
+                bitboard_state = bitboard_game.apply(&bitboard_state, player, action);
+
+                assert_eq!(bitboard_game.to_board(&bitboard_state), *state);
+                assert_eq!(
+                    bitboard_game.evaluate(&bitboard_state, player),
+                    game.evaluate(&state, player)
+                );
+                assert_eq!(
+                    bitboard_game.state_hash(&bitboard_state),
+                    game.state_hash(&state)
+                );
+                let outcome = game.outcome(&state, player);
+                assert_eq!(bitboard_game.outcome(&bitboard_state, player), outcome);
+                if outcome.is_over() {
+                    break;
+                }
+                player = player.opponent();
+            }
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::occupied_cell(vec![(1, 1, X)], O, Move(1, 1))]
+    #[case::out_of_bounds(vec![], X, Move(0, 3))]
+    #[case::game_over(vec![(0, 0, X), (1, 0, O), (0, 1, X), (1, 1, O), (0, 2, X)], O, Move(2, 2))]
+    #[case::wrong_player(vec![(1, 1, X)], X, Move(0, 0))]
+    #[case::valid(vec![(1, 1, X)], O, Move(0, 0))]
+    fn validates_moves_like_the_ndarray_board(
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] player: Player,
+        #[case] action: Move,
+    ) {
+        let game = TicTacToe::init();
+        let bitboard_game = BitboardTicTacToe::init();
+        let state = game.create_state(player_coordinates.clone());
+        let bitboard_state = bitboard_game.create_state(player_coordinates);
+
+        let result = game
+            .try_apply_move(&state, player, action)
+            .map(|state| (*state).clone())
+            .map_err(|error| error.to_string());
+        let bitboard_result = bitboard_game
+            .try_apply_move(&bitboard_state, player, action)
+            .map(|state| bitboard_game.to_board(&state))
+            .map_err(|error| error.to_string());
+        assert_eq!(bitboard_result, result);
+    }
+
+    #[rstest]
+    #[case::too_many_cells(9, 8, 3)]
+    #[case::invalid_win_length(3, 3, 4)]
+    fn invalid_boards_are_rejected(
+        #[case] row_count: usize,
+        #[case] column_count: usize,
+        #[case] win_length: usize,
+    ) {
+        assert!(BitboardTicTacToe::new(row_count, column_count, win_length).is_err());
+    }
+
+    #[test]
+    fn seeded_search_matches_the_ndarray_board() -> Result<()> {
+        let config = MctsConfig::builder().seed(7).build()?;
+        let game = TicTacToe::init();
+        let bitboard_game = BitboardTicTacToe::init();
+
+        let mut tree = Mcts::new(
+            config.clone(),
+            game.clone(),
+            &Position::new(&game, game.initial_state(), X),
+        );
+        let mut bitboard_tree = Mcts::new(
+            config,
+            bitboard_game.clone(),
+            &Position::new(&bitboard_game, bitboard_game.initial_state(), X),
+        );
+
+        assert_eq!(bitboard_tree.search(), tree.search());
+        assert_eq!(bitboard_tree.search_result(), tree.search_result());
+        Ok(())
+    }
+
+    /// Plays uniformly random games from the initial state for the given duration
+    fn rollouts_per_second<G: Game<Player = Player>>(game: &G, duration: Duration) -> f64 {
+        let mut rng = StdRng::seed_from_u64(0);
+        let start = Instant::now();
+        let mut rollout_count = 0;
+
+        while start.elapsed() < duration {
+            let mut state = game.initial_state();
+            let mut player = X;
+            loop {
+                let legal_actions = game.legal_actions(&state);
+                let action = legal_actions[rng.random_range(0..legal_actions.len())];
+                state = game.apply(&state, player, action);
+                if game.outcome(&state, player).is_over() {
+                    break;
+                }
+                player = player.opponent();
+            }
+            rollout_count += 1;
+        }
+
+        rollout_count as f64 / start.elapsed().as_secs_f64()
+    }
+
+    #[rstest]
+    #[case::normal(3, 3, 3)]
+    #[case::gomoku(8, 8, 5)]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored rollouts`"]
+    fn bitboard_rollouts_are_faster(
+        #[case] row_count: usize,
+        #[case] column_count: usize,
+        #[case] win_length: usize,
+    ) -> Result<()> {
+        let duration = Duration::from_secs(1);
+        let ndarray_rate = rollouts_per_second(
+            &TicTacToe::new(row_count, column_count, win_length)?,
+            duration,
+        );
+        let bitboard_rate = rollouts_per_second(
+            &BitboardTicTacToe::new(row_count, column_count, win_length)?,
+            duration,
+        );
+
+        println!(
+            "{}x{} with {} in a row: {:.0} rollouts/s with ndarray, {:.0} rollouts/s with bitboards ({:.1}x)",
+            row_count,
+            column_count,
+            win_length,
+            ndarray_rate,
+            bitboard_rate,
+            bitboard_rate / ndarray_rate
+        );
+        assert!(bitboard_rate > ndarray_rate);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod ZobristHash_tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::games::Player::{O, X};
+    use crate::games::{Board, ConnectFour, Game, Player, TicTacToe, UltimateTicTacToe};
+
+    /// Plays random games, checking after every move that the hash kept up to date by the moves
+    /// is the hash of the board computed from scratch
+    fn assert_incremental_hashes<G: Game<Player = Player>>(
+        game: &G,
+        board: impl Fn(&G::State) -> &Board,
+    ) {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..20 {
+            let mut state = game.initial_state();
+            let mut player = X;
+            while !game.outcome(&state, player.opponent()).is_over() {
+                let legal_actions = game.legal_actions(&state);
+                let action = legal_actions[rng.random_range(0..legal_actions.len())];
+                state = game.apply(&state, player, action);
+
+                let board = board(&state);
+                assert_eq!(
+                    board.zobrist_hash(),
+                    Board::from((**board).clone()).zobrist_hash()
+                );
+                player = player.opponent();
+            }
+        }
+    }
+
+    #[test]
+    fn moves_keep_the_hash_up_to_date() {
+        assert_incremental_hashes(&TicTacToe::init(), |state| state);
+        assert_incremental_hashes(&ConnectFour::init(), |state| state);
+        assert_incremental_hashes(&UltimateTicTacToe::init(), |state| &state.board);
+    }
+
+    #[test]
+    fn move_orders_reaching_the_same_position_share_a_hash() {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 0, X), (1, 1, O), (2, 2, X)]);
+        let transposed_state = game.create_state(vec![(2, 2, X), (1, 1, O), (0, 0, X)]);
+
+        assert_eq!(state, transposed_state);
+        assert_eq!(game.state_hash(&state), game.state_hash(&transposed_state));
+    }
+
+    #[test]
+    fn every_position_of_a_move_has_its_own_hash() {
+        let game = TicTacToe::init();
+        let mut hashes = HashSet::from([game.state_hash(&game.initial_state())]);
+
+        for player in [X, O] {
+            for action in game.legal_actions(&game.initial_state()) {
+                let state = game.apply(&game.initial_state(), player, action);
+                assert!(hashes.insert(game.state_hash(&state)));
+            }
+        }
+    }
+
+    #[test]
+    fn removing_a_piece_restores_the_hash() {
+        let mut board = Board::empty(3, 3);
+        board.place((1, 1), X);
+        board.place((1, 1), O);
+        assert_eq!(
+            board,
+            Board::from(ndarray::array![[0, 0, 0], [0, -1, 0], [0, 0, 0]])
+        );
+
+        board.remove((1, 1));
+        assert_eq!(board.zobrist_hash(), 0);
+    }
+
+    #[test]
+    fn ultimate_hashes_the_sub_board_that_has_to_be_played() {
+        let game = UltimateTicTacToe::init();
+        let state = game.create_state(vec![(4, 4, X)]);
+        let mut free_state = state.clone();
+        free_state.next_sub_board = None;
+
+        assert_eq!(state.board, free_state.board);
+        assert_ne!(game.state_hash(&state), game.state_hash(&free_state));
+    }
+}
+
+#[cfg(test)]
+mod Position_tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rstest::rstest;
+
+    use crate::games::Player::{O, X};
+    use crate::games::{
+        ConnectFour, Game, Move, Outcome, Player, Position, TicTacToe, UltimateTicTacToe,
+    };
+
+    /// Plays random games to the end, then undoes every move, checking that each undo restores
+    /// the position from before that move
+    fn assert_undo_restores_positions<G: Game<Player = Player>>(game: &G) {
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..20 {
+            let mut position = Position::new(game, game.initial_state(), X);
+            let mut positions = vec![];
+            while !position.is_over() {
+                let legal_actions = game.legal_actions(position.state());
+                let action = legal_actions[rng.random_range(0..legal_actions.len())];
+                positions.push((position.clone(), action));
+                position.play(game, action);
+            }
+
+            while let Some((previous_position, action)) = positions.pop() {
+                assert_eq!(position.undo(), Some(action));
+                assert_eq!(position, previous_position);
+                assert_eq!(position.outcome(), Outcome::Ongoing);
+            }
+            assert_eq!(position.undo(), None);
+        }
+    }
+
+    #[test]
+    fn undo_restores_the_position_before_the_move() {
+        assert_undo_restores_positions(&TicTacToe::init());
+        assert_undo_restores_positions(&ConnectFour::init());
+        assert_undo_restores_positions(&UltimateTicTacToe::init());
+    }
+
+    #[test]
+    fn play_passes_the_turn_and_records_the_move() {
+        let game = TicTacToe::init();
+        let mut position = Position::new(&game, game.initial_state(), X);
+
+        position.play(&game, Move(1, 1));
+        position.play(&game, Move(0, 0));
+
+        assert_eq!(position.to_move(), X);
+        assert_eq!(position.last_mover(&game), O);
+        assert_eq!(
+            position.history().collect::<Vec<_>>(),
+            vec![(X, Move(1, 1)), (O, Move(0, 0))]
+        );
+        assert_eq!(
+            *position.state(),
+            game.create_state(vec![(1, 1, X), (0, 0, O)])
+        );
+    }
+
+    #[rstest]
+    fn the_winning_move_decides_the_outcome(#[values(X, O)] player: Player) {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 0, player.opponent()),
+            (1, 1, player.opponent()),
+        ]);
+        let mut position = Position::new(&game, state, player);
+        assert_eq!(position.outcome(), Outcome::Ongoing);
+
+        position.play(&game, Move(0, 2));
+        assert_eq!(position.outcome(), Outcome::Win(player));
+        assert!(position.is_over());
+    }
+
+    #[test]
+    fn a_position_of_a_finished_game_is_over() {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 0, X), (0, 1, X), (0, 2, X), (1, 0, O), (1, 1, O)]);
+
+        assert_eq!(Position::new(&game, state, O).outcome(), Outcome::Win(X));
+    }
+
+    #[test]
+    fn positions_are_equal_however_they_were_reached() {
+        let game = TicTacToe::init();
+        let mut position = Position::new(&game, game.initial_state(), X);
+        position.play(&game, Move(0, 0));
+        position.play(&game, Move(1, 1));
+        position.play(&game, Move(2, 2));
+
+        let transposed = Position::new(&game, game.initial_state(), X)
+            .child(&game, Move(2, 2))
+            .child(&game, Move(1, 1))
+            .child(&game, Move(0, 0));
+
+        assert_eq!(transposed.history().count(), 0);
+        assert_eq!(position, transposed);
+        assert_ne!(
+            position,
+            Position::new(&game, transposed.state().clone(), X)
+        );
+    }
+}
@@ -1,3 +1,23 @@
 #![allow(non_snake_case)]
 #[cfg(test)]
+mod arena_tests;
+#[cfg(test)]
+mod config_tests;
+#[cfg(test)]
+mod final_move_tests;
+#[cfg(test)]
+mod games_tests;
+#[cfg(test)]
+mod outcome_tests;
+#[cfg(test)]
+mod parallel_tests;
+#[cfg(test)]
+mod rollout_tests;
+#[cfg(test)]
+mod selection_tests;
+#[cfg(test)]
 mod simple_MCTS_tests;
+#[cfg(test)]
+mod solver_tests;
+#[cfg(test)]
+mod symmetry_tests;
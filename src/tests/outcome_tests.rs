@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod Outcome_tests {
+    use rstest::rstest;
+
+    use anyhow::{Context, Ok, Result};
+
+    use crate::config::MctsConfig;
+    use crate::games::Player::{O, X};
+    use crate::games::{
+        ConnectFour, Game, Move, Outcome, Player, Position, TicTacToe, UltimateTicTacToe,
+    };
+    use crate::mcts::Mcts;
+
+    #[rstest]
+    #[case::win(Outcome::Win(player), 1.0, true)]
+    #[case::loss(Outcome::Win(player.opponent()), -1.0, true)]
+    #[case::draw(Outcome::Draw, 0.0, true)]
+    #[case::ongoing(Outcome::Ongoing, 0.0, false)]
+    fn rewards_are_from_the_perspective_of_the_given_player(
+        #[values(X, O)] player: Player,
+        #[case] outcome: Outcome<Player>,
+        #[case] reward: f32,
+        #[case] is_over: bool,
+    ) {
+        assert_eq!(outcome.reward(player), reward);
+        assert_eq!(outcome.reward(player.opponent()), -reward);
+        assert_eq!(outcome.is_over(), is_over);
+    }
+
+    #[rstest]
+    fn the_last_mover_wins_every_game(#[values(X, O)] player: Player) {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 0, player), (0, 1, player), (0, 2, player)]);
+        assert_eq!(game.outcome(&state, player), Outcome::Win(player));
+
+        let game = ConnectFour::init();
+        let state = game.create_state(vec![(3, player), (3, player), (3, player), (3, player)]);
+        assert_eq!(game.outcome(&state, player), Outcome::Win(player));
+
+        let game = UltimateTicTacToe::init();
+        let mut state = game.get_initial_state();
+        for sub_board in 0..3 {
+            state.meta_board[[sub_board, 0]] = player.cell();
+        }
+        assert_eq!(game.outcome(&state, player), Outcome::Win(player));
+    }
+
+    #[rstest]
+    fn a_full_board_without_a_winner_is_a_draw(#[values(X, O)] player: Player) {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player.opponent()),
+            (0, 2, player),
+            (1, 0, player),
+            (1, 1, player.opponent()),
+            (1, 2, player.opponent()),
+            (2, 0, player.opponent()),
+            (2, 1, player),
+            (2, 2, player),
+        ]);
+
+        assert_eq!(game.outcome(&state, player), Outcome::Draw);
+        assert_eq!(game.outcome(&state, player.opponent()), Outcome::Draw);
+    }
+
+    #[rstest]
+    fn mcts_values_wins_losses_and_draws_for_the_player_to_move(
+        #[values(X, O)] player: Player,
+    ) -> Result<()> {
+        let game = TicTacToe::init();
+        let config = MctsConfig::builder().seed(42).build()?;
+        let mean_values = |state| {
+            let mut tree = Mcts::new(
+                config.clone(),
+                TicTacToe::init(),
+                &Position::new(&game, state, player),
+            );
+            tree.search();
+            tree.search_result()
+                .actions
+                .into_iter()
+                .map(|statistics| (statistics.action, statistics.mean_value))
+                .collect::<Vec<_>>()
+        };
+
+        // Every search of the immediate win ends in a win
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 1, player.opponent()),
+        ]);
+        let win = mean_values(state)
+            .into_iter()
+            .find(|&(action, _)| action == Move(0, 2))
+            .context("The win was not searched")?;
+        assert_eq!(win.1, 1.0);
+
+        // The opponent threatens both (0, 2) and (2, 0), so every move loses
+        let state = game.create_state(vec![
+            (0, 0, player.opponent()),
+            (0, 1, player.opponent()),
+            (1, 0, player.opponent()),
+            (1, 1, player),
+            (2, 1, player),
+        ]);
+        assert!(mean_values(state)
+            .iter()
+            .all(|&(_, mean_value)| mean_value < 0.0));
+
+        // Blocking the middle column leaves a single move, which draws
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player.opponent()),
+            (0, 2, player),
+            (1, 0, player),
+            (1, 1, player.opponent()),
+            (1, 2, player.opponent()),
+            (2, 0, player.opponent()),
+        ]);
+        for (action, mean_value) in mean_values(state) {
+            match action {
+                Move(2, 1) => assert_eq!(mean_value, 0.0),
+                _ => assert!(mean_value < 0.0),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod Parallel_tests {
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::config::{MctsConfig, Parallelism};
+    use crate::games::Player::{O, X};
+    use crate::games::{ConnectFour, Move, Player, Position, TicTacToe};
+    use crate::mcts::Mcts;
+    use crate::parallel::ParallelMcts;
+
+    #[test]
+    fn a_single_thread_searches_like_a_single_tree() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let config = MctsConfig::builder().seed(3).build()?;
+
+        let mut tree = Mcts::new(
+            config.clone(),
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), X),
+        );
+        let mut parallel =
+            ParallelMcts::new(config, TicTacToe::init(), &Position::new(&game, state, X));
+
+        assert_eq!(parallel.search(), tree.search());
+        assert_eq!(parallel.search_result(), tree.search_result());
+        Ok(())
+    }
+
+    #[test]
+    fn every_tree_gets_its_own_seed() -> Result<()> {
+        let game = TicTacToe::init();
+        let config = MctsConfig::builder().seed(10).num_threads(4).build()?;
+        let parallel = ParallelMcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        let seeds: Vec<_> = parallel.trees().iter().map(|tree| tree.seed()).collect();
+        assert_eq!(seeds, vec![Some(10), Some(11), Some(12), Some(13)]);
+        Ok(())
+    }
+
+    #[rstest]
+    fn merged_statistics_pick_the_winning_move(#[values(X, O)] player: Player) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 1, player.opponent()),
+        ]);
+        let config = MctsConfig::builder()
+            .num_searches(200)
+            .num_threads(4)
+            .seed(42)
+            .build()?;
+        let mut parallel = ParallelMcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), player),
+        );
+
+        let best_action = parallel.search();
+        let result = parallel.search_result();
+
+        assert_eq!(best_action, Move(0, 2));
+        let visit_count: u32 = result.actions.iter().map(|action| action.visit_count).sum();
+        assert_eq!(visit_count, 4 * 200);
+        assert_eq!(result.principal_variation.first(), Some(&best_action));
+
+        parallel.advance(best_action);
+        assert_eq!(
+            parallel.root_position().state(),
+            &game.apply_move(&state, player, best_action)
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    fn shared_tree_picks_the_winning_move(#[values(X, O)] player: Player) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 1, player.opponent()),
+        ]);
+        let config = MctsConfig::builder()
+            .num_searches(400)
+            .num_threads(4)
+            .parallelism(Parallelism::Tree)
+            .seed(42)
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state, player),
+        );
+
+        let best_action = tree.search();
+        let result = tree.search_result();
+
+        assert_eq!(best_action, Move(0, 2));
+        // The workers share the search limit and every search reaches the root
+        assert_eq!(tree.root_visit_count(), 400);
+        let visit_count: u32 = result.actions.iter().map(|action| action.visit_count).sum();
+        assert_eq!(visit_count, 400);
+        Ok(())
+    }
+
+    #[test]
+    fn shared_tree_with_transpositions_counts_every_search() -> Result<()> {
+        let game = TicTacToe::init();
+        let config = MctsConfig::builder()
+            .num_searches(2000)
+            .num_threads(4)
+            .parallelism(Parallelism::Tree)
+            .transpositions(true)
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        tree.search();
+
+        let visit_count: u32 = tree
+            .search_result()
+            .actions
+            .iter()
+            .map(|action| action.visit_count)
+            .sum();
+        assert_eq!(visit_count, 2000);
+        assert!(tree.node_count() <= 2001);
+        Ok(())
+    }
+
+    #[test]
+    fn tree_parallelization_searches_a_single_tree() -> Result<()> {
+        let game = ConnectFour::init();
+        let config = MctsConfig::builder()
+            .num_searches(200)
+            .num_threads(4)
+            .parallelism(Parallelism::Tree)
+            .build()?;
+        let mut parallel = ParallelMcts::new(
+            config,
+            ConnectFour::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        let best_action = parallel.search();
+
+        assert_eq!(parallel.trees().len(), 1);
+        assert_eq!(parallel.trees()[0].root_visit_count(), 200);
+        assert!(best_action < 7);
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod RolloutPolicy_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::config::{MctsConfig, Rollout};
+    use crate::games::Player::{O, X};
+    use crate::games::{Game, Move, Player, Position, TicTacToe};
+    use crate::mcts::Mcts;
+    use crate::rollout::{self, EpsilonGreedyRollout, RolloutPolicy, WinOrBlockRollout};
+
+    #[rstest]
+    #[case::wins(vec![(0, 0, player), (0, 1, player), (1, 0, player.opponent()), (1, 1, player.opponent())], Move(0, 2))]
+    #[case::blocks(vec![(0, 0, player.opponent()), (0, 1, player.opponent()), (1, 1, player)], Move(0, 2))]
+    #[case::wins_before_blocking(vec![(1, 0, player), (1, 1, player), (0, 0, player.opponent()), (0, 1, player.opponent())], Move(1, 2))]
+    fn win_or_block_plays_the_decisive_move(
+        #[values(X, O)] player: Player,
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] expected_action: Move,
+    ) {
+        let game = TicTacToe::init();
+        let state = game.create_state(player_coordinates);
+        let legal_actions = game.legal_actions(&state);
+
+        for seed in 0..10 {
+            let action = WinOrBlockRollout.choose_action(
+                &game,
+                &state,
+                player,
+                &legal_actions,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            assert_eq!(action, expected_action);
+        }
+    }
+
+    #[test]
+    fn greedy_rollout_takes_the_center_of_an_empty_board() {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let legal_actions = game.legal_actions(&state);
+
+        let action = EpsilonGreedyRollout { epsilon: 0.0 }.choose_action(
+            &game,
+            &state,
+            X,
+            &legal_actions,
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert_eq!(action, Move(1, 1));
+    }
+
+    #[rstest]
+    fn evaluation_is_the_opposite_for_the_opponent(#[values(X, O)] player: Player) {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(1, 1, player), (0, 1, player.opponent())]);
+
+        let value = game.evaluate(&state, player);
+        assert!(value > 0.0 && value <= 1.0);
+        assert_eq!(game.evaluate(&state, player.opponent()), -value);
+        assert_eq!(game.evaluate(&game.get_initial_state(), player), 0.0);
+    }
+
+    #[rstest]
+    #[case::uniform(Rollout::Uniform)]
+    #[case::win_or_block(Rollout::WinOrBlock)]
+    #[case::epsilon_greedy(Rollout::EpsilonGreedy { epsilon: 0.1 })]
+    #[case::truncated(Rollout::Truncated { depth: 2 })]
+    fn every_rollout_policy_finds_the_winning_move(
+        #[values(X, O)] player: Player,
+        #[case] rollout: Rollout,
+    ) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 1, player.opponent()),
+        ]);
+        let config = MctsConfig::builder().seed(42).rollout(rollout).build()?;
+
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), player),
+        );
+        let best_action = tree.search();
+        assert!(game.check_win(&game.apply_move(&state, player, best_action), player));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::uniform(Rollout::Uniform, None)]
+    #[case::truncated(Rollout::Truncated { depth: 3 }, Some(3))]
+    fn only_truncated_rollouts_stop_early(
+        #[case] rollout: Rollout,
+        #[case] max_depth: Option<usize>,
+    ) {
+        let policy: Box<dyn RolloutPolicy<TicTacToe>> = rollout::from_config(rollout);
+        assert_eq!(policy.max_depth(), max_depth);
+    }
+}
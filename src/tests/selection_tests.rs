@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod SelectionPolicy_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::config::{MctsConfig, Selection};
+    use crate::games::Player::{O, X};
+    use crate::games::{Player, Position, TicTacToe};
+    use crate::mcts::Mcts;
+    use crate::play_interface::head_to_head;
+    use crate::selection::{
+        ChildStatistics, EpsilonGreedy, Puct, SelectionPolicy, Thompson, Ucb1, Ucb1Tuned,
+    };
+
+    #[rstest]
+    #[case::ucb1(Selection::Ucb1)]
+    #[case::ucb1_tuned(Selection::Ucb1Tuned)]
+    #[case::puct(Selection::Puct)]
+    #[case::thompson(Selection::Thompson)]
+    #[case::epsilon_greedy(Selection::EpsilonGreedy { epsilon: 0.2 })]
+    fn every_selection_policy_finds_the_winning_move(
+        #[values(X, O)] player: Player,
+        #[case] selection: Selection,
+    ) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 1, player.opponent()),
+        ]);
+        let config = MctsConfig::builder()
+            .seed(42)
+            .selection(selection)
+            .build()?;
+
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), player),
+        );
+        let best_action = tree.search();
+        assert!(game.check_win(&game.apply_move(&state, player, best_action), player));
+        Ok(())
+    }
+
+    #[test]
+    fn ucb1_selects_unvisited_children_first() {
+        let children = [child(10.0, 0.9, 0.9, 0.5), child(0.0, 0.0, 0.0, 0.5)];
+        let policy = Ucb1 {
+            exploration_constant: 1.0,
+        };
+
+        assert_eq!(policy.select(10.0, &children, &mut rng()), 1);
+    }
+
+    #[test]
+    fn ucb1_tuned_explores_children_with_varying_values() {
+        // Both have a mean value of 0.0, but only the first always draws
+        let children = [child(1000.0, 0.0, 0.0, 0.5), child(1000.0, 0.0, 1.0, 0.5)];
+        let policy = Ucb1Tuned {
+            exploration_constant: 1.0,
+        };
+
+        assert_eq!(policy.select(2000.0, &children, &mut rng()), 1);
+    }
+
+    #[test]
+    fn puct_explores_children_with_a_high_prior() {
+        let children = [child(5.0, 0.0, 0.0, 0.1), child(5.0, 0.0, 0.0, 0.9)];
+        let policy = Puct {
+            exploration_constant: 1.0,
+        };
+
+        assert_eq!(policy.select(10.0, &children, &mut rng()), 1);
+    }
+
+    #[test]
+    fn thompson_sampling_prefers_the_child_that_won_more() {
+        let children = [child(100.0, -0.8, 0.8, 0.5), child(100.0, 0.8, 0.8, 0.5)];
+
+        for seed in 0..10 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            assert_eq!(Thompson.select(200.0, &children, &mut rng), 1);
+        }
+    }
+
+    #[rstest]
+    #[case::greedy(0.0, vec![2])]
+    #[case::random(1.0, vec![0, 1, 2])]
+    fn epsilon_greedy_selects_the_best_child_or_a_random_one(
+        #[case] epsilon: f32,
+        #[case] expected_children: Vec<usize>,
+    ) {
+        let children = [
+            child(10.0, -0.5, 0.5, 0.3),
+            child(10.0, 0.0, 0.5, 0.3),
+            child(10.0, 0.5, 0.5, 0.3),
+        ];
+        let policy = EpsilonGreedy { epsilon };
+
+        let mut rng = rng();
+        let mut selected: Vec<usize> = (0..100)
+            .map(|_| policy.select(30.0, &children, &mut rng))
+            .collect();
+        selected.sort();
+        selected.dedup();
+        assert_eq!(selected, expected_children);
+    }
+
+    #[rstest]
+    #[case::ucb1_tuned(Selection::Ucb1Tuned)]
+    #[case::puct(Selection::Puct)]
+    #[case::thompson(Selection::Thompson)]
+    fn selection_policies_play_matches_against_ucb1(#[case] selection: Selection) -> Result<()> {
+        let ucb1 = MctsConfig::builder().seed(42).num_searches(200).build()?;
+        let challenger = MctsConfig::builder()
+            .seed(42)
+            .num_searches(200)
+            .selection(selection)
+            .build()?;
+
+        let result = head_to_head(&TicTacToe::init(), &challenger, &ucb1, 4);
+        assert_eq!(result.wins + result.draws + result.losses, 4);
+        Ok(())
+    }
+
+    fn child(
+        visit_count: f32,
+        mean_value: f32,
+        mean_squared_value: f32,
+        prior: f32,
+    ) -> ChildStatistics {
+        ChildStatistics {
+            visit_count,
+            mean_value,
+            mean_squared_value,
+            prior,
+        }
+    }
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(42)
+    }
+}
@@ -1,13 +1,20 @@
 #[cfg(test)]
 mod MCTS_tests {
     use rstest::rstest;
-    use std::collections::HashMap;
+    use std::collections::HashSet;
+    use std::thread;
+    use std::time::{Duration, Instant};
 
-    use anyhow::{Ok, Result};
-    use ndarray::Array2;
+    use anyhow::{Context, Ok, Result};
+    use serde_json::Value;
 
-    use crate::games::TicTacToe;
-    use crate::mcts::Mcts;
+    use crate::config::{MctsConfig, RaveSchedule};
+    use crate::games::Player::{O, X};
+    use crate::games::{Board, ConnectFour, Move, Outcome, Player, Position, TicTacToe};
+    use crate::mcts::{ExportOptions, Mcts, Proof};
+    use crate::solver::Solver;
+
+    const DEFAULT_TEST_SEED: u64 = 42;
 
     #[rstest]
     #[case::board0(vec![(0, 1, player), (0, 2, player)] )]
@@ -23,12 +30,12 @@ mod MCTS_tests {
     #[case::board8(vec![(2, 0, player), (2, 1, player)] )]
     //
     fn tictactoe_coords_with_one_winning_move(
-        #[values(1, -1)] player: i8,
-        #[case] player_coordinates: Vec<(usize, usize, i8)>,
+        #[values(X, O)] player: Player,
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
     ) -> Result<()> {
         let game = TicTacToe::init();
-        let args: HashMap<&str, f32> =
-            HashMap::from([("C", f32::sqrt(2.0)), ("num_searches", 1000.0)]);
+        let seed = test_seed();
+        let config = seeded_config(seed)?;
 
         #[allow(clippy::unwrap_used)]
         let state = game.create_state(player_coordinates);
@@ -36,62 +43,681 @@ mod MCTS_tests {
         let mut state = state.clone();
         game.print_state(&state)?;
 
-        let best_action = get_best_action(args.clone(), TicTacToe::init(), &state, player);
+        let best_action = get_best_action(config.clone(), TicTacToe::init(), &state, player);
 
         state = game.apply_move(&state, player, best_action);
 
         game.print_state(&state)?;
-        assert!(game.check_win(&state, player));
+        assert!(game.check_win(&state, player), "Failed with seed {}", seed);
         Ok(())
     }
 
     #[rstest]
-    #[case::board(vec![(1, 1, -player)] )]
+    #[case::board(vec![(1, 1, player.opponent())] )]
     fn tictactoe_mcts_always_wins_or_draws(
-        #[values(1, -1)] player: i8,
-        #[case] player_coordinates: Vec<(usize, usize, i8)>,
+        #[values(X, O)] player: Player,
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
     ) -> Result<()> {
         let game = TicTacToe::init();
-        let args: HashMap<&str, f32> =
-            HashMap::from([("C", f32::sqrt(2.0)), ("num_searches", 1000.0)]);
+        let seed = test_seed();
 
         #[allow(clippy::unwrap_used)]
         let state = game.create_state(player_coordinates);
 
         let mut num_non_draws = 0;
 
-        for _ in 0..100 {
+        for game_index in 0..100 {
             let mut state = state.clone();
             let mut rollout_player = player;
-            loop {
+            for move_index in 0.. {
+                // Every search gets its own seed, derived from the seed of the test
+                let config = seeded_config(seed.wrapping_add(game_index * 10 + move_index))?;
                 let best_action =
-                    get_best_action(args.clone(), TicTacToe::init(), &state, rollout_player);
+                    get_best_action(config, TicTacToe::init(), &state, rollout_player);
                 state = game.apply_move(&state, rollout_player, best_action);
 
-                let (value, terminated) = game.get_value_and_terminated(&state, rollout_player);
+                let outcome = game.get_outcome(&state, rollout_player);
 
-                if terminated {
-                    if value != 0.5 {
+                if outcome.is_over() {
+                    if outcome != Outcome::Draw {
                         num_non_draws += 1;
                     }
                     break;
                 }
-                rollout_player = -rollout_player;
+                rollout_player = rollout_player.opponent();
             }
         }
 
         println!("{}", num_non_draws);
-        assert!(num_non_draws == 0);
+        assert!(num_non_draws == 0, "Failed with seed {}", seed);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::block_row(vec![(0, 0, player.opponent()), (1, 1, player), (0, 1, player.opponent())])]
+    #[case::block_diagonal(vec![(1, 1, player.opponent()), (0, 1, player), (2, 2, player.opponent())])]
+    #[case::win_over_block(vec![(0, 0, player), (1, 0, player.opponent()), (0, 1, player), (1, 1, player.opponent())])]
+    fn tictactoe_mcts_plays_a_move_the_solver_proves_optimal(
+        #[values(X, O)] player: Player,
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+    ) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(player_coordinates);
+        let optimal_moves = Solver::new(TicTacToe::init())
+            .solve(&state, player)
+            .optimal_moves;
+
+        let seed = test_seed();
+        let best_action = get_best_action(seeded_config(seed)?, TicTacToe::init(), &state, player);
+
+        assert!(
+            optimal_moves.contains(&best_action),
+            "{:?} is not one of {:?}, failed with seed {}",
+            best_action,
+            optimal_moves,
+            seed
+        );
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::row(vec![(0, player), (1, player), (2, player), (0, player.opponent()), (1, player.opponent())], 3)]
+    #[case::column(vec![(5, player), (5, player), (5, player), (0, player.opponent()), (6, player.opponent())], 5)]
+    fn connect_four_takes_the_winning_column(
+        #[values(X, O)] player: Player,
+        #[case] player_columns: Vec<(usize, Player)>,
+        #[case] winning_column: usize,
+    ) -> Result<()> {
+        let game = ConnectFour::init();
+        let state = game.create_state(player_columns);
+
+        let seed = test_seed();
+        let mut tree = Mcts::new(
+            seeded_config(seed)?,
+            ConnectFour::init(),
+            &Position::new(&game, state.clone(), player),
+        );
+        let best_action = tree.search();
+
+        let state = game.apply_move(&state, player, best_action);
+        assert_eq!(best_action, winning_column, "Failed with seed {}", seed);
+        assert!(game.check_win(&state, player));
+        Ok(())
+    }
+
+    #[test]
+    fn identical_seeds_build_identical_trees() -> Result<()> {
+        let game = ConnectFour::init();
+        let state = game.get_initial_state();
+        let search = || -> Result<_> {
+            let mut tree = Mcts::new(
+                seeded_config(7)?,
+                ConnectFour::init(),
+                &Position::new(&game, state.clone(), X),
+            );
+            let best_action = tree.search();
+            tree.advance(best_action);
+            Ok((
+                best_action,
+                tree.node_count(),
+                tree.root_visit_count(),
+                tree.seed(),
+            ))
+        };
+
+        assert_eq!(search()?, search()?);
+        Ok(())
+    }
+
+    #[test]
+    fn random_seeds_are_remembered() -> Result<()> {
+        let game = TicTacToe::init();
+        let mut tree = Mcts::new(
+            MctsConfig::default(),
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        #[allow(clippy::unwrap_used)]
+        let seed = tree.seed().unwrap();
+        let mut replayed = Mcts::new(
+            seeded_config(seed)?,
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+        assert_eq!(replayed.seed(), Some(seed));
+
+        // Replaying the remembered seed searches exactly the same way
+        assert_eq!(
+            replayed.search(),
+            tree.search(),
+            "Failed with seed {}",
+            seed
+        );
+        assert_eq!(replayed.search_result(), tree.search_result());
+        assert_eq!(
+            replayed.to_dot(ExportOptions::default()),
+            tree.to_dot(ExportOptions::default())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn advancing_keeps_the_statistics_of_the_played_child() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let config = MctsConfig::builder().num_searches(1000).build()?;
+        let mut tree = Mcts::new(
+            config.clone(),
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), X),
+        );
+
+        let best_action = tree.search();
+        let node_count = tree.node_count();
+        tree.advance(best_action);
+
+        let state = game.apply_move(&state, X, best_action);
+        assert_eq!(tree.root_position().state(), &state);
+        assert!(tree.root_visit_count() > 0);
+        assert!(tree.node_count() < node_count);
+
+        // Searching again builds on top of the kept visits
+        let visit_count = tree.root_visit_count();
+        let opponent_action = tree.search();
+        assert_eq!(
+            Some(tree.root_visit_count() - visit_count),
+            config.num_searches()
+        );
+
+        tree.advance(opponent_action);
+        let state = game.apply_move(&state, O, opponent_action);
+        assert_eq!(tree.root_position().state(), &state);
         Ok(())
     }
 
+    #[test]
+    fn advancing_to_an_unexpanded_action_starts_a_new_tree() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let config = MctsConfig::builder().num_searches(1).build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), X),
+        );
+
+        let expanded_action = tree.search();
+        let unexpanded_action = game
+            .get_legal_moves(&state)
+            .into_iter()
+            .find(|&action| action != expanded_action);
+
+        #[allow(clippy::unwrap_used)]
+        let unexpanded_action = unexpanded_action.unwrap();
+        tree.advance(unexpanded_action);
+
+        assert_eq!(tree.node_count(), 1);
+        assert_eq!(tree.root_visit_count(), 0);
+        assert_eq!(
+            tree.root_position().state(),
+            &game.apply_move(&state, X, unexpanded_action)
+        );
+
+        // The root knows the opponent is the one to move next
+        let opponent_action = tree.search();
+        tree.advance(opponent_action);
+        assert_eq!(
+            tree.root_position().state()[[opponent_action.0, opponent_action.1]],
+            -1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn search_stops_at_the_node_limit() -> Result<()> {
+        let game = TicTacToe::init();
+        let config = MctsConfig::builder().max_nodes(50).build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        tree.search();
+
+        assert_eq!(tree.node_count(), 50);
+        Ok(())
+    }
+
+    #[test]
+    fn search_stops_at_the_time_limit() -> Result<()> {
+        let game = ConnectFour::init();
+        let config = MctsConfig::builder()
+            .max_duration(Duration::from_millis(50))
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            ConnectFour::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        let start = Instant::now();
+        tree.search();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(tree.root_visit_count() > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn anytime_search_returns_once_stopped_from_another_thread() {
+        let game = ConnectFour::init();
+        let mut tree = Mcts::new(
+            MctsConfig::default(),
+            ConnectFour::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+        let stop_handle = tree.stop_handle();
+
+        let best_action = thread::scope(|scope| {
+            let search = scope.spawn(|| tree.search_until_stopped());
+            thread::sleep(Duration::from_millis(50));
+            stop_handle.stop();
+            search.join()
+        });
+
+        assert!(best_action.is_ok_and(|action| action < 7));
+    }
+
+    #[test]
+    fn search_result_matches_the_chosen_action() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 0, X), (0, 1, X), (1, 1, O)]);
+        let mut tree = Mcts::new(
+            seeded_config(test_seed())?,
+            TicTacToe::init(),
+            &Position::new(&game, state, X),
+        );
+
+        let best_action = tree.search();
+        let result = tree.search_result();
+
+        #[allow(clippy::unwrap_used)]
+        let best = result.best_action().unwrap();
+        assert_eq!(best.action, best_action);
+        assert_eq!(result.principal_variation.first(), Some(&best_action));
+        assert!(best.mean_value > 0.9);
+
+        // Every legal move has been tried and the visits of the root's children add up
+        assert_eq!(result.actions.len(), 6);
+        let visit_count: u32 = result.actions.iter().map(|action| action.visit_count).sum();
+        assert_eq!(visit_count, tree.root_visit_count());
+        assert!(result
+            .actions
+            .iter()
+            .all(|action| action.prior == 1.0 / 6.0));
+        assert!(result
+            .actions
+            .windows(2)
+            .all(|pair| pair[0].visit_count >= pair[1].visit_count));
+
+        assert_eq!(result.node_count, tree.node_count());
+        assert!(result.max_depth >= result.principal_variation.len());
+        Ok(())
+    }
+
+    #[test]
+    fn search_result_of_an_unsearched_tree_is_empty() {
+        let game = TicTacToe::init();
+        let tree = Mcts::new(
+            MctsConfig::default(),
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+
+        let result = tree.search_result();
+
+        assert!(result.best_action().is_none());
+        assert!(result.principal_variation.is_empty());
+        assert_eq!((result.node_count, result.max_depth), (1, 0));
+    }
+
+    #[test]
+    fn dot_export_contains_every_node_and_edge() -> Result<()> {
+        let game = TicTacToe::init();
+        let mut tree = Mcts::new(
+            seeded_config(test_seed())?,
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+        tree.search();
+
+        let dot = tree.to_dot(ExportOptions::default());
+
+        assert!(dot.starts_with("digraph mcts {"));
+        assert!(dot.contains("0 [label=\"root\\nvisits: 1000"));
+        assert_eq!(dot.matches("visits: ").count(), tree.node_count());
+        assert_eq!(dot.matches(" -> ").count(), tree.node_count() - 1);
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::max_depth(ExportOptions { max_depth: Some(1), min_visits: 0 })]
+    #[case::min_visits(ExportOptions { max_depth: None, min_visits: 50 })]
+    #[case::both(ExportOptions { max_depth: Some(2), min_visits: 10 })]
+    fn json_export_is_truncated(#[case] options: ExportOptions) -> Result<()> {
+        let game = TicTacToe::init();
+        let mut tree = Mcts::new(
+            seeded_config(test_seed())?,
+            TicTacToe::init(),
+            &Position::new(&game, game.get_initial_state(), X),
+        );
+        tree.search();
+
+        let exported: Value = serde_json::from_str(&tree.to_json(options)?)?;
+        let nodes = exported["nodes"].as_array().context("Missing nodes")?;
+
+        assert!(nodes.len() > 1 && nodes.len() < tree.node_count());
+        assert_eq!(nodes[0]["index"], 0);
+        assert_eq!(nodes[0]["visit_count"], 1000);
+        for node in &nodes[1..] {
+            let depth = node["depth"].as_u64().context("Missing depth")? as usize;
+            let visit_count = node["visit_count"].as_u64().context("Missing visits")?;
+            assert!(options.max_depth.is_none_or(|max_depth| depth <= max_depth));
+            assert!(visit_count >= options.min_visits as u64);
+            assert!(node["board"].is_object());
+        }
+
+        // Children that were left out are not referenced
+        let num_children: usize = nodes
+            .iter()
+            .filter_map(|node| node["children"].as_array())
+            .map(|children| children.len())
+            .sum();
+        assert_eq!(num_children, nodes.len() - 1);
+        Ok(())
+    }
+
+    #[test]
+    fn transpositions_share_nodes_between_move_orders() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let search = |transpositions: bool| -> Result<Mcts<TicTacToe>> {
+            let config = MctsConfig::builder()
+                .seed(test_seed())
+                .transpositions(transpositions)
+                .build()?;
+            let mut tree = Mcts::new(
+                config,
+                TicTacToe::init(),
+                &Position::new(&game, state.clone(), X),
+            );
+            tree.search();
+            Ok(tree)
+        };
+
+        let tree = search(false)?;
+        let graph = search(true)?;
+        assert!(graph.node_count() < tree.node_count());
+        assert_eq!(graph.root_visit_count(), 1000);
+
+        // Every position has one node, which can be reached through several parents
+        let exported: Value = serde_json::from_str(&graph.to_json(ExportOptions::default())?)?;
+        let nodes = exported["nodes"].as_array().context("Missing nodes")?;
+        let positions: HashSet<String> = nodes
+            .iter()
+            .map(|node| format!("{} {}", node["board"], node["to_move"]))
+            .collect();
+        let num_edges: usize = nodes
+            .iter()
+            .filter_map(|node| node["children"].as_array())
+            .map(|children| children.len())
+            .sum();
+
+        assert_eq!(nodes.len(), graph.node_count());
+        assert_eq!(positions.len(), nodes.len());
+        assert!(num_edges > nodes.len() - 1);
+        Ok(())
+    }
+
+    #[rstest]
+    fn transpositions_find_the_winning_move_after_advancing(
+        #[values(X, O)] player: Player,
+    ) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 0, player), (1, 1, player.opponent())]);
+        let config = MctsConfig::builder()
+            .seed(test_seed())
+            .transpositions(true)
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state.clone(), player),
+        );
+        tree.search();
+
+        // The opponent plays badly, leaving the top row open
+        tree.advance(Move(0, 1));
+        let state = game.apply_move(&state, player, Move(0, 1));
+        tree.advance(Move(2, 2));
+        let state = game.apply_move(&state, player.opponent(), Move(2, 2));
+        assert_eq!(tree.root_position().state(), &state);
+
+        let best_action = tree.search();
+        let state = game.apply_move(&state, player, best_action);
+        assert!(
+            game.check_win(&state, player),
+            "Failed with seed {}",
+            test_seed()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merged_symmetric_moves_are_searched_once() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let config = MctsConfig::builder()
+            .seed(test_seed())
+            .merge_symmetric_moves(true)
+            .build()?;
+        let mut tree = Mcts::new(config, TicTacToe::init(), &Position::new(&game, state, X));
+
+        tree.search();
+
+        // A corner, an edge and the center
+        let result = tree.search_result();
+        assert_eq!(result.actions.len(), 3);
+        assert!(result
+            .actions
+            .iter()
+            .any(|statistics| statistics.action == Move(1, 1)));
+        Ok(())
+    }
+
+    #[rstest]
+    #[case::equivalence(RaveSchedule::Equivalence { equivalence: 300.0 })]
+    #[case::minimum_error(RaveSchedule::MinimumError { bias: 0.1 })]
+    fn rave_blocks_the_winning_move_of_the_opponent(
+        #[values(X, O)] player: Player,
+        #[case] schedule: RaveSchedule,
+    ) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player.opponent()),
+            (0, 1, player.opponent()),
+            (1, 1, player),
+        ]);
+        let config = MctsConfig::builder()
+            .seed(test_seed())
+            .num_searches(200)
+            .rave(schedule)
+            .build()?;
+
+        let best_action = get_best_action(config, TicTacToe::init(), &state, player);
+        assert_eq!(best_action, Move(0, 2), "Failed with seed {}", test_seed());
+        Ok(())
+    }
+
+    #[test]
+    fn rave_statistics_count_moves_played_later_on() -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.get_initial_state();
+        let search = |rave: bool| -> Result<Mcts<TicTacToe>> {
+            let mut builder = MctsConfig::builder().seed(test_seed());
+            if rave {
+                builder = builder.rave(RaveSchedule::Equivalence { equivalence: 300.0 });
+            }
+            let mut tree = Mcts::new(
+                builder.build()?,
+                TicTacToe::init(),
+                &Position::new(&game, state.clone(), X),
+            );
+            tree.search();
+            Ok(tree)
+        };
+
+        assert!(search(false)?
+            .root_rave_statistics()
+            .iter()
+            .all(|&(_, rave_visit_count, _)| rave_visit_count == 0));
+
+        // Every search plays each action at most once, either directly or later on
+        let statistics = search(true)?.root_rave_statistics();
+        assert_eq!(statistics.len(), 9);
+        for (action, rave_visit_count, rave_value_sum) in statistics {
+            assert!(rave_visit_count > 0, "{:?} has no RAVE visits", action);
+            assert!(rave_visit_count <= 1000);
+            assert!(rave_value_sum.abs() <= rave_visit_count as f32);
+        }
+        Ok(())
+    }
+
+    #[rstest]
+    fn solver_proves_the_immediate_win(#[values(X, O)] player: Player) -> Result<()> {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player),
+            (1, 1, player.opponent()),
+        ]);
+        let config = MctsConfig::builder()
+            .seed(test_seed())
+            .solver(true)
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state, player),
+        );
+
+        let best_action = tree.search();
+        assert_eq!(best_action, Move(0, 2));
+        let result = tree.search_result();
+        let win = result
+            .actions
+            .iter()
+            .find(|statistics| statistics.action == Move(0, 2))
+            .context("The win was not searched")?;
+        assert_eq!(win.proof, Proof::Win);
+
+        // The search stops once the root is proven
+        assert!(tree.root_visit_count() < 1000);
+        Ok(())
+    }
+
+    #[rstest]
+    fn solver_proves_a_forced_loss(#[values(X, O)] player: Player) -> Result<()> {
+        // The opponent threatens both (0, 2) and (2, 0)
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player.opponent()),
+            (0, 1, player.opponent()),
+            (1, 0, player.opponent()),
+            (1, 1, player),
+            (2, 1, player),
+        ]);
+        let config = MctsConfig::builder()
+            .seed(test_seed())
+            .solver(true)
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state, player),
+        );
+
+        tree.search();
+        let result = tree.search_result();
+        assert_eq!(result.actions.len(), 4);
+        assert!(result
+            .actions
+            .iter()
+            .all(|statistics| statistics.proof == Proof::Loss));
+        assert!(tree.root_visit_count() < 1000);
+        Ok(())
+    }
+
+    #[rstest]
+    fn solver_avoids_the_proven_loss_for_the_proven_draw(
+        #[values(X, O)] player: Player,
+    ) -> Result<()> {
+        // Only (2, 1) stops the opponent from completing the middle column
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![
+            (0, 0, player),
+            (0, 1, player.opponent()),
+            (0, 2, player),
+            (1, 0, player),
+            (1, 1, player.opponent()),
+            (1, 2, player.opponent()),
+            (2, 0, player.opponent()),
+        ]);
+        let config = MctsConfig::builder()
+            .seed(test_seed())
+            .solver(true)
+            .build()?;
+        let mut tree = Mcts::new(
+            config,
+            TicTacToe::init(),
+            &Position::new(&game, state, player),
+        );
+
+        assert_eq!(tree.search(), Move(2, 1));
+        let proofs: Vec<(Move, Proof)> = tree
+            .search_result()
+            .actions
+            .iter()
+            .map(|statistics| (statistics.action, statistics.proof))
+            .collect();
+        assert!(proofs.contains(&(Move(2, 1), Proof::Draw)));
+        assert!(proofs.contains(&(Move(2, 2), Proof::Loss)));
+        Ok(())
+    }
+
+    /// The seed used by the searches of the tests, a failing test can be replayed with its seed by
+    /// setting the `MCTS_SEED` environment variable
+    fn test_seed() -> u64 {
+        std::env::var("MCTS_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or(DEFAULT_TEST_SEED)
+    }
+
+    fn seeded_config(seed: u64) -> Result<MctsConfig> {
+        MctsConfig::builder().seed(seed).build()
+    }
+
     fn get_best_action(
-        args: HashMap<&str, f32>,
+        config: MctsConfig,
         game: TicTacToe,
-        given_state: &Array2<i8>,
-        player: i8,
-    ) -> (usize, usize) {
-        let mut tree = Mcts::new(args, game, given_state, player);
+        given_state: &Board,
+        player: Player,
+    ) -> Move {
+        let root = Position::new(&game, given_state.clone(), player);
+        let mut tree = Mcts::new(config, game, &root);
         tree.search()
     }
 }
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod Solver_tests {
+    use rstest::rstest;
+
+    use crate::games::Player::{O, X};
+    use crate::games::{Move, Player, TicTacToe};
+    use crate::solver::Solver;
+
+    #[test]
+    fn empty_board_is_a_draw_from_every_move() {
+        let game = TicTacToe::init();
+        let mut solver = Solver::new(TicTacToe::init());
+
+        let solved = solver.solve(&game.get_initial_state(), X);
+
+        assert_eq!(solved.value, 0);
+        assert_eq!(solved.optimal_moves.len(), 9);
+    }
+
+    #[rstest]
+    fn only_corners_answer_the_center(#[values(X, O)] player: Player) {
+        let game = TicTacToe::init();
+        let mut solver = Solver::new(TicTacToe::init());
+        let state = game.create_state(vec![(1, 1, player.opponent())]);
+
+        let mut solved = solver.solve(&state, player);
+        solved.optimal_moves.sort();
+
+        assert_eq!(solved.value, 0);
+        assert_eq!(
+            solved.optimal_moves,
+            vec![Move(0, 0), Move(0, 2), Move(2, 0), Move(2, 2)]
+        );
+    }
+
+    #[rstest]
+    #[case::win_in_one(vec![(0, 0, X), (1, 0, O), (0, 1, X), (1, 1, O)], X, 1, vec![Move(0, 2)])]
+    #[case::must_block(vec![(0, 0, X), (1, 1, O), (0, 1, X)], O, 0, vec![Move(0, 2)])]
+    #[case::fork(vec![(0, 0, X), (1, 1, O), (2, 2, X), (0, 2, O), (2, 0, X)], O, -1, vec![Move(0, 1), Move(1, 0), Move(1, 2), Move(2, 1)])]
+    #[case::full_board_draw(vec![(0, 0, X), (0, 1, O), (0, 2, X), (1, 1, O), (1, 0, X), (1, 2, O), (2, 1, X), (2, 0, O), (2, 2, X)], O, 0, vec![])]
+    fn solves_known_positions(
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] player: Player,
+        #[case] value: i8,
+        #[case] optimal_moves: Vec<Move>,
+    ) {
+        let game = TicTacToe::init();
+        let mut solver = Solver::new(TicTacToe::init());
+        let state = game.create_state(player_coordinates);
+
+        let mut solved = solver.solve(&state, player);
+        solved.optimal_moves.sort();
+
+        assert_eq!(solved.value, value);
+        assert_eq!(solved.optimal_moves, optimal_moves);
+    }
+
+    #[test]
+    fn symmetric_positions_are_remembered_once() {
+        let game = TicTacToe::init();
+        let mut solver = Solver::new(TicTacToe::init());
+
+        solver.solve(&game.create_state(vec![(0, 0, X)]), O);
+        let memo_size = solver.memo_size();
+
+        // Every other corner opening is a rotation of the first one
+        for corner in [(0, 2), (2, 0), (2, 2)] {
+            let solved = solver.solve(&game.create_state(vec![(corner.0, corner.1, X)]), O);
+            assert_eq!(solved.value, 0);
+            assert_eq!(solved.optimal_moves, vec![Move(1, 1)]);
+        }
+        assert_eq!(solver.memo_size(), memo_size);
+    }
+}
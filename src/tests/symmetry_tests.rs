@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod Symmetry_tests {
+    use rstest::rstest;
+
+    use anyhow::{Ok, Result};
+
+    use crate::games::Player::{O, X};
+    use crate::games::{ConnectFour, Game, Player, TicTacToe};
+    use crate::symmetry::{canonical_form, Symmetry};
+
+    #[rstest]
+    fn symmetries_move_cells_with_their_positions(
+        #[values(
+            Symmetry::Identity,
+            Symmetry::Rotate90,
+            Symmetry::Rotate180,
+            Symmetry::Rotate270,
+            Symmetry::FlipRows,
+            Symmetry::FlipColumns,
+            Symmetry::Transpose,
+            Symmetry::AntiTranspose
+        )]
+        symmetry: Symmetry,
+    ) {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 1, X), (1, 2, O), (2, 2, X)]);
+
+        let symmetric_state = symmetry.apply_to_state(&state);
+
+        for (position, &cell) in state.indexed_iter() {
+            let (row, column) = symmetry.apply_to_position(position, 3, 3);
+            assert_eq!(symmetric_state[[row, column]], cell);
+        }
+        assert_eq!(symmetry.inverse().apply_to_state(&symmetric_state), *state);
+    }
+
+    #[test]
+    fn symmetric_boards_share_a_canonical_form() {
+        let game = TicTacToe::init();
+        let state = game.create_state(vec![(0, 0, X), (0, 1, O), (1, 1, X), (2, 1, O)]);
+        let (canonical_state, symmetry) = canonical_form(&state);
+
+        assert_eq!(symmetry.apply_to_state(&state), canonical_state);
+        for other_symmetry in Symmetry::ALL {
+            let symmetric_state = other_symmetry.apply_to_state(&state);
+            assert_eq!(canonical_form(&symmetric_state).0, canonical_state);
+        }
+    }
+
+    #[test]
+    fn rectangular_boards_only_keep_their_shape() -> Result<()> {
+        let game = TicTacToe::new(3, 4, 3)?;
+        let state = game.create_state(vec![(0, 0, X), (1, 3, O)]);
+
+        let (canonical_state, _) = canonical_form(&state);
+
+        assert_eq!(canonical_state.dim(), (3, 4));
+        assert_eq!(Symmetry::of_board(3, 4).len(), 4);
+        Ok(())
+    }
+
+    #[rstest]
+    // Corners, edges and the center
+    #[case::empty(vec![], 3)]
+    // Only mirroring along the diagonal through the corner keeps the board the same
+    #[case::corner(vec![(0, 0, X)], 5)]
+    #[case::no_symmetry(vec![(0, 0, X), (0, 1, O)], 7)]
+    fn tictactoe_merges_symmetric_moves(
+        #[case] player_coordinates: Vec<(usize, usize, Player)>,
+        #[case] num_distinct_moves: usize,
+    ) {
+        let game = TicTacToe::init();
+        let state = game.create_state(player_coordinates);
+
+        let distinct_moves = game.distinct_actions(&state, game.get_legal_moves(&state));
+
+        assert_eq!(distinct_moves.len(), num_distinct_moves);
+    }
+
+    #[test]
+    fn connect_four_merges_mirrored_columns() {
+        let game = ConnectFour::init();
+        let state = game.get_initial_state();
+        assert_eq!(
+            game.distinct_actions(&state, game.get_legal_moves(&state)),
+            vec![0, 1, 2, 3]
+        );
+
+        let state = game.create_state(vec![(0, X)]);
+        assert_eq!(
+            game.distinct_actions(&state, game.get_legal_moves(&state))
+                .len(),
+            7
+        );
+    }
+}